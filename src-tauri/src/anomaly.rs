@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::sys::ProcessInfo;
+
+/// Original parent pid of processes that were reparented while we watched, keyed by pid,
+/// with the start time that tells a reused pid apart.
+pub type Reparented = HashMap<u32, (Option<u64>, u32)>;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct Zombie {
+    pub pid: u32,
+    pub name: Option<String>,
+    /// The parent that hasn't reaped it yet.
+    pub ppid: Option<u32>,
+    pub parent_name: Option<String>,
+    pub parent_status: Option<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ReaperKind {
+    Init,
+    /// A process that set `PR_SET_CHILD_SUBREAPER`, or the init of a nested pid namespace.
+    Subreaper,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct Orphan {
+    pub pid: u32,
+    pub name: Option<String>,
+    pub ppid: u32,
+    pub original_ppid: u32,
+    pub reaper: ReaperKind,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct MissingParent {
+    pub pid: u32,
+    pub children: Vec<u32>,
+    /// The parent was in the previous snapshot, so it exited while this one was taken.
+    pub vanished: bool,
+    /// Last known name, when it vanished.
+    pub name: Option<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ProcessAnomalies {
+    pub zombies: Vec<Zombie>,
+    /// Only processes reparented while tr-process was running can be told apart from
+    /// children started by init directly.
+    pub orphans: Vec<Orphan>,
+    pub missing_parents: Vec<MissingParent>,
+}
+
+fn is_live(p: &ProcessInfo) -> bool {
+    !p.is_missing()
+}
+
+/// Records processes whose parent changed between two snapshots and forgets the ones that exited.
+pub fn track_reparented(reparented: &mut Reparented, prev: &HashMap<u32, ProcessInfo>, cur: &HashMap<u32, ProcessInfo>) {
+    reparented.retain(|pid, (start_time, _)| cur.get(pid).is_some_and(|p| p.start_time == *start_time));
+    for p in cur.values().filter(|p| is_live(p)) {
+        let Some(before) = prev.get(&p.pid).filter(|b| is_live(b) && b.start_time == p.start_time) else { continue };
+        if let Some(original) = before.ppid.filter(|ppid| Some(*ppid) != p.ppid) {
+            reparented.entry(p.pid).or_insert((p.start_time, original));
+        }
+    }
+}
+
+pub fn report(processes: &HashMap<u32, ProcessInfo>, prev: &HashMap<u32, ProcessInfo>, reparented: &Reparented) -> ProcessAnomalies {
+    let live = |pid: u32| processes.get(&pid).filter(|p| is_live(p));
+
+    let mut zombies: Vec<Zombie> = processes.values()
+        .filter(|p| p.status.as_deref() == Some("Zombie"))
+        .map(|p| {
+            let parent = p.ppid.and_then(live);
+            Zombie {
+                pid: p.pid,
+                name: p.name.clone(),
+                ppid: p.ppid,
+                parent_name: parent.and_then(|parent| parent.name.clone()),
+                parent_status: parent.and_then(|parent| parent.status.clone()),
+            }
+        })
+        .collect();
+    zombies.sort_by_key(|z| z.pid);
+
+    let mut orphans: Vec<Orphan> = reparented.iter()
+        .filter_map(|(pid, (_, original_ppid))| {
+            let p = live(*pid)?;
+            let ppid = p.ppid?;
+            Some(Orphan {
+                pid: *pid,
+                name: p.name.clone(),
+                ppid,
+                original_ppid: *original_ppid,
+                reaper: if ppid == 1 { ReaperKind::Init } else { ReaperKind::Subreaper },
+            })
+        })
+        .collect();
+    orphans.sort_by_key(|o| o.pid);
+
+    let mut children: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for p in processes.values().filter(|p| is_live(p)) {
+        if let Some(ppid) = p.ppid.filter(|ppid| live(*ppid).is_none()) {
+            children.entry(ppid).or_default().push(p.pid);
+        }
+    }
+    let missing_parents = children.into_iter().map(|(pid, mut children)| {
+        children.sort();
+        let before = prev.get(&pid).filter(|b| is_live(b));
+        MissingParent { pid, children, vanished: before.is_some(), name: before.and_then(|b| b.name.clone()) }
+    }).collect();
+
+    ProcessAnomalies { zombies, orphans, missing_parents }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo { pid, ppid: Some(ppid), name: Some(name.to_string()), start_time: Some(pid as u64), ..ProcessInfo::default() }
+    }
+
+    fn snapshot(processes: Vec<ProcessInfo>) -> HashMap<u32, ProcessInfo> {
+        processes.into_iter().map(|p| (p.pid, p)).collect()
+    }
+
+    #[test]
+    fn test_report() {
+        let init = ProcessInfo { ppid: None, ..process(1, 0, "init") };
+        let prev = snapshot(vec![
+            init.clone(),
+            process(10, 1, "shell"),
+            process(11, 10, "worker"),
+            process(20, 1, "server"),
+            process(21, 20, "handler"),
+        ]);
+        let mut zombie = process(12, 1, "defunct");
+        zombie.status = Some("Zombie".to_string());
+        let cur = snapshot(vec![
+            init,
+            process(11, 1, "worker"),
+            zombie,
+            process(21, 20, "handler"),
+            ProcessInfo { pid: 20, missing: Some(true), ..ProcessInfo::default() },
+        ]);
+        let mut reparented = Reparented::new();
+        track_reparented(&mut reparented, &prev, &cur);
+        let anomalies = report(&cur, &prev, &reparented);
+
+        assert_eq!(anomalies.zombies.len(), 1);
+        assert_eq!(anomalies.zombies[0].parent_name.as_deref(), Some("init"));
+        assert_eq!(anomalies.orphans.len(), 1);
+        assert_eq!(anomalies.orphans[0].original_ppid, 10);
+        assert_eq!(anomalies.orphans[0].reaper, ReaperKind::Init);
+        assert_eq!(anomalies.missing_parents.len(), 1);
+        assert_eq!(anomalies.missing_parents[0].children, vec![21]);
+        assert!(anomalies.missing_parents[0].vanished);
+        assert_eq!(anomalies.missing_parents[0].name.as_deref(), Some("server"));
+    }
+}
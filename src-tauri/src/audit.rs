@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+/// Requests kept in the audit log.
+pub const AUDIT_CAPACITY: usize = 1000;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Client address, without the port.
+    pub client: Option<String>,
+    pub method: String,
+    pub route: String,
+    /// From `Content-Length`; unset for streamed bodies.
+    pub payload_size: Option<u64>,
+    pub status: u16,
+    pub latency_ms: u64,
+}
+
+/// The most recent API requests, oldest first.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn record(&mut self, entry: AuditEntry) {
+        if self.entries.len() >= AUDIT_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_newest() {
+        let mut log = AuditLog::default();
+        for i in 0..AUDIT_CAPACITY as u64 + 5 {
+            log.record(AuditEntry {
+                timestamp: i,
+                client: Some("127.0.0.1".to_string()),
+                method: "POST".to_string(),
+                route: "/emit".to_string(),
+                payload_size: Some(17),
+                status: 200,
+                latency_ms: 1,
+            });
+        }
+        let entries = log.entries();
+        assert_eq!(entries.len(), AUDIT_CAPACITY);
+        assert_eq!(entries[0].timestamp, 5);
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use tr_process_lib::error::Result;
+use tr_process_lib::monitor::Monitor;
+use tr_process_lib::sys::{self, ProcessInfo, SockInfo, SockProtocol, SockState};
+use tr_process_lib::wait::{self, WaitCondition, WaitRequest};
+
+#[derive(Parser)]
+#[command(name = "tr-process-cli", version, about = "List processes and sockets without the tr-process window")]
+struct Cli {
+    #[arg(long, value_enum, default_value_t = Format::Table, global = true)]
+    format: Format,
+    /// Refresh every N seconds until interrupted
+    #[arg(long, global = true)]
+    watch: Option<u64>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List processes
+    Ps(ProcessArgs),
+    /// Show processes as a parent/child tree
+    Tree(ProcessArgs),
+    /// List TCP and UDP sockets
+    Sockets(SocketArgs),
+    /// List listening TCP ports and bound UDP ports
+    Ports(SocketArgs),
+    /// Block until a condition holds; exits with 1 on timeout
+    Wait(WaitArgs),
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SortKey {
+    Pid,
+    Ppid,
+    Name,
+    Cpu,
+    Memory,
+    Pss,
+    Uptime,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SockSortKey {
+    Port,
+    Pid,
+    Proto,
+    State,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Proto {
+    Tcp,
+    Udp,
+}
+
+#[derive(Args)]
+struct ProcessArgs {
+    /// Only processes whose name contains this text (case-insensitive)
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long)]
+    pid: Option<u32>,
+    /// Only processes with a socket on this local port
+    #[arg(long)]
+    port: Option<u16>,
+    #[arg(long, value_enum, default_value_t = SortKey::Pid)]
+    sort: SortKey,
+    #[arg(long)]
+    desc: bool,
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args)]
+struct SocketArgs {
+    #[arg(long)]
+    port: Option<u16>,
+    #[arg(long)]
+    pid: Option<u32>,
+    #[arg(long, value_enum)]
+    proto: Option<Proto>,
+    /// Only processes whose name contains this text (case-insensitive)
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long, value_enum, default_value_t = SockSortKey::Port)]
+    sort: SockSortKey,
+    #[arg(long)]
+    desc: bool,
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args)]
+struct WaitArgs {
+    #[command(flatten)]
+    condition: WaitConditionArgs,
+    /// Protocol for --port; either by default
+    #[arg(long, value_enum)]
+    proto: Option<Proto>,
+    /// Seconds to wait before giving up
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct WaitConditionArgs {
+    /// A TCP socket is listening on (or a UDP socket is bound to) this port
+    #[arg(long)]
+    port: Option<u16>,
+    /// A process whose name contains this text exists (case-insensitive)
+    #[arg(long)]
+    process: Option<String>,
+    /// The process with this pid has exited
+    #[arg(long)]
+    exited: Option<u32>,
+    /// No process whose name contains this text exists (case-insensitive)
+    #[arg(long)]
+    no_process: Option<String>,
+}
+
+impl WaitArgs {
+    fn condition(&self) -> WaitCondition {
+        let c = &self.condition;
+        if let Some(port) = c.port {
+            let protocol = self.proto.map(|p| match p {
+                Proto::Tcp => SockProtocol::Tcp,
+                Proto::Udp => SockProtocol::Udp,
+            });
+            WaitCondition::PortListening { port, protocol }
+        } else if let Some(name) = &c.process {
+            WaitCondition::ProcessExists { name: name.clone() }
+        } else if let Some(pid) = c.exited {
+            WaitCondition::ProcessExited { pid }
+        } else {
+            WaitCondition::NoProcess { name: c.no_process.clone().unwrap_or_default() }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SockRow {
+    #[serde(flatten)]
+    sock: SockInfo,
+    names: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct TreeNode {
+    #[serde(flatten)]
+    info: ProcessInfo,
+    children: Vec<TreeNode>,
+}
+
+fn name_matches(name: Option<&str>, filter: &Option<String>) -> bool {
+    match filter {
+        Some(filter) => name.is_some_and(|n| n.to_lowercase().contains(&filter.to_lowercase())),
+        None => true,
+    }
+}
+
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{}B", bytes) } else { format!("{:.1}{}", value, UNITS[unit]) }
+}
+
+fn human_duration(secs: u64) -> String {
+    let (d, h, m, s) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if d > 0 { format!("{}d{:02}h", d, h) } else { format!("{:02}:{:02}:{:02}", h, m, s) }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_output(format: Format, headers: &[&str], rows: Vec<Vec<String>>, json: Value) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default()),
+        Format::Csv => {
+            println!("{}", headers.join(","));
+            for row in rows {
+                println!("{}", row.iter().map(|f| csv_field(f)).collect::<Vec<String>>().join(","));
+            }
+        }
+        Format::Table => {
+            let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+            for row in rows.iter() {
+                for (i, field) in row.iter().enumerate() {
+                    widths[i] = widths[i].max(field.chars().count());
+                }
+            }
+            let line = |fields: Vec<String>| {
+                let padded: Vec<String> = fields.iter().enumerate()
+                    .map(|(i, f)| format!("{:<width$}", f, width = widths[i]))
+                    .collect();
+                println!("{}", padded.join("  ").trim_end());
+            };
+            line(headers.iter().map(|h| h.to_string()).collect());
+            for row in rows {
+                line(row);
+            }
+        }
+    }
+}
+
+fn ports(info: &ProcessInfo) -> String {
+    let mut ports: Vec<u16> = info.socks.iter()
+        .filter(|s| matches!(s.state, Some(SockState::Listen) | None))
+        .map(|s| s.local_port)
+        .collect();
+    ports.sort();
+    ports.dedup();
+    ports.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+fn process_row(info: &ProcessInfo, format: Format, name: String) -> Vec<String> {
+    let human = format == Format::Table;
+    vec![
+        info.pid.to_string(),
+        info.ppid.map(|p| p.to_string()).unwrap_or_default(),
+        name,
+        info.cpu_usage.map(|c| format!("{:.1}", c)).unwrap_or_default(),
+        info.memory.map(|m| if human { human_bytes(m) } else { m.to_string() }).unwrap_or_default(),
+        info.uptime.map(|u| if human { human_duration(u) } else { u.to_string() }).unwrap_or_default(),
+        ports(info),
+    ]
+}
+
+const PROCESS_HEADERS: [&str; 7] = ["PID", "PPID", "NAME", "CPU%", "MEM", "UPTIME", "PORTS"];
+
+fn filter_processes(processes: Vec<ProcessInfo>, args: &ProcessArgs) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = processes.into_iter()
+        .filter(|p| name_matches(p.name.as_deref(), &args.name))
+        .filter(|p| args.pid.is_none_or(|pid| p.pid == pid))
+        .filter(|p| args.port.is_none_or(|port| p.socks.iter().any(|s| s.local_port == port)))
+        .collect();
+    processes.sort_by(|a, b| {
+        let ord = match args.sort {
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::Ppid => a.ppid.cmp(&b.ppid),
+            SortKey::Name => a.name.as_deref().map(str::to_lowercase).cmp(&b.name.as_deref().map(str::to_lowercase)),
+            SortKey::Cpu => a.cpu_usage.unwrap_or_default().total_cmp(&b.cpu_usage.unwrap_or_default()),
+            SortKey::Memory => a.memory.cmp(&b.memory),
+            SortKey::Pss => a.memory_detail.as_ref().map(|m| m.pss).cmp(&b.memory_detail.as_ref().map(|m| m.pss)),
+            SortKey::Uptime => a.uptime.cmp(&b.uptime),
+        };
+        if args.desc { ord.reverse() } else { ord }
+    });
+    if let Some(limit) = args.limit {
+        processes.truncate(limit);
+    }
+    processes
+}
+
+fn show_processes(processes: Vec<ProcessInfo>, args: &ProcessArgs, format: Format) {
+    let processes = filter_processes(processes, args);
+    let rows = processes.iter()
+        .map(|p| process_row(p, format, p.name.clone().unwrap_or_default()))
+        .collect();
+    print_output(format, &PROCESS_HEADERS, rows, serde_json::to_value(&processes).unwrap_or_default());
+}
+
+fn build_tree(pid: u32, by_parent: &HashMap<u32, Vec<ProcessInfo>>, info: ProcessInfo) -> TreeNode {
+    let children = by_parent.get(&pid)
+        .map(|children| children.iter().map(|c| build_tree(c.pid, by_parent, c.clone())).collect())
+        .unwrap_or_default();
+    TreeNode { info, children }
+}
+
+fn flatten_tree(node: &TreeNode, depth: usize, format: Format, rows: &mut Vec<Vec<String>>) {
+    let name = node.info.name.clone().unwrap_or_default();
+    let name = match format {
+        Format::Table if depth > 0 => format!("{}└─ {}", "   ".repeat(depth - 1), name),
+        Format::Csv => format!("{}{}", ".".repeat(depth), name),
+        _ => name,
+    };
+    rows.push(process_row(&node.info, format, name));
+    for child in node.children.iter() {
+        flatten_tree(child, depth + 1, format, rows);
+    }
+}
+
+fn show_tree(processes: Vec<ProcessInfo>, args: &ProcessArgs, format: Format) {
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    let matched: Vec<u32> = filter_processes(processes.clone(), args).iter().map(|p| p.pid).collect();
+    let mut by_parent: HashMap<u32, Vec<ProcessInfo>> = HashMap::new();
+    for p in processes.iter() {
+        if let Some(ppid) = p.ppid.filter(|ppid| pids.contains(ppid)) {
+            by_parent.entry(ppid).or_default().push(p.clone());
+        }
+    }
+    for children in by_parent.values_mut() {
+        children.sort_by_key(|c| c.pid);
+    }
+    // roots are the matched processes whose parent did not match
+    let mut roots: Vec<ProcessInfo> = processes.into_iter()
+        .filter(|p| matched.contains(&p.pid))
+        .filter(|p| !p.ppid.is_some_and(|ppid| matched.contains(&ppid)))
+        .collect();
+    roots.sort_by_key(|p| p.pid);
+    let trees: Vec<TreeNode> = roots.into_iter().map(|r| build_tree(r.pid, &by_parent, r)).collect();
+
+    let mut rows = vec![];
+    for tree in trees.iter() {
+        flatten_tree(tree, 0, format, &mut rows);
+    }
+    print_output(format, &PROCESS_HEADERS, rows, serde_json::to_value(&trees).unwrap_or_default());
+}
+
+fn show_sockets(processes: &[ProcessInfo], sockets: Vec<SockInfo>, args: &SocketArgs, format: Format, listening: bool) {
+    let names: HashMap<u32, String> = processes.iter()
+        .filter_map(|p| p.name.clone().map(|n| (p.pid, n)))
+        .collect();
+    let mut rows: Vec<SockRow> = sockets.into_iter()
+        .filter(|s| !listening || matches!(s.state, Some(SockState::Listen) | None))
+        .filter(|s| args.port.is_none_or(|port| s.local_port == port || s.remote_port == Some(port)))
+        .filter(|s| args.pid.is_none_or(|pid| s.pids.contains(&pid)))
+        .filter(|s| match args.proto {
+            Some(Proto::Tcp) => matches!(s.protocol, SockProtocol::Tcp),
+            Some(Proto::Udp) => matches!(s.protocol, SockProtocol::Udp),
+            None => true,
+        })
+        .map(|s| {
+            let names = s.pids.iter().filter_map(|pid| names.get(pid).cloned()).collect();
+            SockRow { sock: s, names }
+        })
+        .filter(|r| args.name.is_none() || r.names.iter().any(|n| name_matches(Some(n), &args.name)))
+        .collect();
+    rows.sort_by(|a, b| {
+        let ord = match args.sort {
+            SockSortKey::Port => a.sock.local_port.cmp(&b.sock.local_port),
+            SockSortKey::Pid => a.sock.pids.first().cmp(&b.sock.pids.first()),
+            SockSortKey::Proto => format!("{:?}", a.sock.protocol).cmp(&format!("{:?}", b.sock.protocol)),
+            SockSortKey::State => format!("{:?}", a.sock.state).cmp(&format!("{:?}", b.sock.state)),
+        };
+        if args.desc { ord.reverse() } else { ord }
+    });
+    if let Some(limit) = args.limit {
+        rows.truncate(limit);
+    }
+
+    let headers = ["PROTO", "LOCAL", "PORT", "REMOTE", "RPORT", "STATE", "PID", "NAME"];
+    let table: Vec<Vec<String>> = rows.iter().map(|r| vec![
+        format!("{:?}", r.sock.protocol),
+        r.sock.local_addr.clone(),
+        r.sock.local_port.to_string(),
+        r.sock.remote_addr.clone().unwrap_or_default(),
+        r.sock.remote_port.map(|p| p.to_string()).unwrap_or_default(),
+        r.sock.state.as_ref().map(|s| format!("{:?}", s)).unwrap_or_default(),
+        r.sock.pids.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(" "),
+        r.names.join(" "),
+    ]).collect();
+    print_output(format, &headers, table, serde_json::to_value(&rows).unwrap_or_default());
+}
+
+fn show(cli: &Cli, monitor: &mut Monitor) -> Result<()> {
+    monitor.refresh()?;
+    let processes: Vec<ProcessInfo> = monitor.processes().into_iter().filter(|p| !p.is_missing()).collect();
+    match &cli.command {
+        Command::Ps(args) => show_processes(processes, args, cli.format),
+        Command::Tree(args) => show_tree(processes, args, cli.format),
+        Command::Sockets(args) => show_sockets(&processes, sys::get_sockets()?, args, cli.format, false),
+        Command::Ports(args) => show_sockets(&processes, sys::get_sockets()?, args, cli.format, true),
+        Command::Wait(_) => {}
+    }
+    Ok(())
+}
+
+fn run_wait(args: &WaitArgs, format: Format) -> Result<bool> {
+    let result = wait::wait(&WaitRequest {
+        condition: args.condition(),
+        timeout_ms: Some(args.timeout.saturating_mul(1000)),
+    })?;
+    let pids = result.pids.iter().map(|p| p.to_string()).collect::<Vec<String>>().join(" ");
+    let row = vec![result.satisfied.to_string(), result.elapsed_ms.to_string(), pids];
+    print_output(format, &["SATISFIED", "ELAPSED_MS", "PID"], vec![row], serde_json::to_value(&result).unwrap_or_default());
+    Ok(result.satisfied)
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Command::Wait(args) = &cli.command {
+        match run_wait(args, cli.format) {
+            Ok(satisfied) => std::process::exit(if satisfied { 0 } else { 1 }),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+    let mut monitor = Monitor::new();
+    // cpu usage needs two samples
+    let _ = monitor.refresh();
+    thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+    loop {
+        if cli.watch.is_some() && cli.format == Format::Table {
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Err(e) = show(&cli, &mut monitor) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        match cli.watch {
+            Some(secs) => thread::sleep(Duration::from_secs(secs.max(1))),
+            None => break,
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::{ApiError, Result};
+use crate::smaps;
+use crate::sys::ProcessInfo;
+
+pub const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ContainerRuntime {
+    Docker,
+    Containerd,
+    Podman,
+    Crio,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CgroupInfo {
+    /// cgroup v2 path relative to the mount, e.g. `/system.slice/docker-<id>.scope`.
+    pub path: String,
+    pub container_id: Option<String>,
+    pub runtime: Option<ContainerRuntime>,
+    /// Innermost systemd `.service` or `.scope`.
+    pub unit: Option<String>,
+    /// Innermost systemd `.slice`.
+    pub slice: Option<String>,
+}
+
+/// A cgroup with its direct members; the totals include every descendant group and
+/// `memory` sums PSS where known.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CgroupNode {
+    pub path: String,
+    pub container_id: Option<String>,
+    pub runtime: Option<ContainerRuntime>,
+    pub unit: Option<String>,
+    pub pids: Vec<u32>,
+    pub process_count: u32,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub children: Vec<CgroupNode>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Limit {
+    Max,
+    Value(u64),
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CpuMax {
+    /// Microseconds of CPU time per period; `Max` means unthrottled.
+    pub quota: Limit,
+    pub period: u64,
+}
+
+/// `cpu.stat`; times are in microseconds.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CpuStat {
+    pub usage_usec: Option<u64>,
+    pub user_usec: Option<u64>,
+    pub system_usec: Option<u64>,
+    pub nr_periods: Option<u64>,
+    pub nr_throttled: Option<u64>,
+    pub throttled_usec: Option<u64>,
+    /// Share of enforcement periods in which the group was throttled.
+    pub throttled_percent: Option<f32>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PressureLine {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    /// Total stall time in microseconds.
+    pub total: u64,
+}
+
+/// Pressure stall information from a `*.pressure` file.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Pressure {
+    pub some: Option<PressureLine>,
+    pub full: Option<PressureLine>,
+}
+
+/// Limits, usage and pressure of one cgroup v2 group; `None` where the controller isn't enabled.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CgroupStats {
+    pub path: String,
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<Limit>,
+    pub memory_high: Option<Limit>,
+    pub cpu_max: Option<CpuMax>,
+    pub cpu_stat: Option<CpuStat>,
+    pub pids_current: Option<u64>,
+    pub pids_max: Option<Limit>,
+    pub cpu_pressure: Option<Pressure>,
+    pub memory_pressure: Option<Pressure>,
+    pub io_pressure: Option<Pressure>,
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() >= 12 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn container_of(segment: &str, parent: Option<&str>) -> Option<(String, Option<ContainerRuntime>)> {
+    let name = segment.strip_suffix(".scope").unwrap_or(segment);
+    const PREFIXES: [(&str, ContainerRuntime); 4] = [
+        ("docker-", ContainerRuntime::Docker),
+        ("cri-containerd-", ContainerRuntime::Containerd),
+        ("libpod-", ContainerRuntime::Podman),
+        ("crio-", ContainerRuntime::Crio),
+    ];
+    for (prefix, runtime) in PREFIXES {
+        if let Some(id) = name.strip_prefix(prefix) {
+            // conmon runs next to the container in libpod-conmon-<id>.scope
+            return is_container_id(id).then(|| (id.to_string(), Some(runtime)));
+        }
+    }
+    if !is_container_id(name) {
+        return None;
+    }
+    // cgroupfs driver: /docker/<id>, /libpod_parent/<id>, /kubepods/<qos>/pod<uid>/<id>
+    let runtime = match parent? {
+        "docker" => Some(ContainerRuntime::Docker),
+        "libpod_parent" => Some(ContainerRuntime::Podman),
+        _ => None,
+    };
+    Some((name.to_string(), runtime))
+}
+
+/// Derives container and systemd details from a cgroup v2 path.
+pub fn parse_path(path: &str) -> CgroupInfo {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let mut info = CgroupInfo { path: path.to_string(), ..CgroupInfo::default() };
+    for (i, segment) in segments.iter().enumerate().rev() {
+        if info.container_id.is_none() {
+            let parent = i.checked_sub(1).map(|p| segments[p]);
+            if let Some((id, runtime)) = container_of(segment, parent) {
+                info.container_id = Some(id);
+                info.runtime = runtime;
+            }
+        }
+        if info.unit.is_none() && (segment.ends_with(".service") || segment.ends_with(".scope")) {
+            info.unit = Some(segment.to_string());
+        }
+        if info.slice.is_none() && segment.ends_with(".slice") {
+            info.slice = Some(segment.to_string());
+        }
+    }
+    info
+}
+
+/// Picks the unified hierarchy entry (`0::<path>`) out of `/proc/<pid>/cgroup`.
+pub fn parse_proc_cgroup(content: &str) -> Option<String> {
+    content.lines().find_map(|line| line.strip_prefix("0::")).map(|p| p.to_string())
+}
+
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<CgroupInfo> {
+    let content = std::fs::read_to_string(crate::procfs::proc_path(pid, "cgroup")).ok()?;
+    parse_proc_cgroup(&content).map(|path| parse_path(&path))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<CgroupInfo> {
+    None
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    if path == "/" {
+        return None;
+    }
+    match path.rfind('/') {
+        Some(0) => Some("/"),
+        Some(i) => Some(&path[..i]),
+        None => Some("/"),
+    }
+}
+
+/// Groups processes by cgroup and returns the hierarchy rooted at `/`.
+pub fn hierarchy(processes: &[ProcessInfo]) -> CgroupNode {
+    let mut nodes: BTreeMap<String, CgroupNode> = BTreeMap::new();
+    nodes.insert("/".to_string(), CgroupNode { path: "/".to_string(), ..CgroupNode::default() });
+    for p in processes.iter() {
+        let Some(cgroup) = p.cgroup.as_ref() else { continue };
+        let mut path = Some(cgroup.path.as_str());
+        while let Some(current) = path {
+            if nodes.contains_key(current) {
+                break;
+            }
+            let info = parse_path(current);
+            nodes.insert(current.to_string(), CgroupNode {
+                path: current.to_string(),
+                container_id: info.container_id,
+                runtime: info.runtime,
+                unit: info.unit,
+                ..CgroupNode::default()
+            });
+            path = parent_path(current);
+        }
+        if let Some(node) = nodes.get_mut(&cgroup.path) {
+            node.pids.push(p.pid);
+            node.process_count += 1;
+            node.cpu_usage += p.cpu_usage.unwrap_or_default();
+            node.memory += smaps::pss_or_memory(p);
+        }
+    }
+
+    // deepest paths first, so each node is complete before it moves into its parent
+    let mut paths: Vec<String> = nodes.keys().cloned().collect();
+    paths.sort_by_key(|p| std::cmp::Reverse(p.matches('/').count()));
+    for path in paths {
+        let Some(parent) = parent_path(&path).map(|p| p.to_string()) else { continue };
+        let Some(mut node) = nodes.remove(&path) else { continue };
+        node.pids.sort();
+        node.children.sort_by(|a, b| a.path.cmp(&b.path));
+        if let Some(parent) = nodes.get_mut(&parent) {
+            parent.process_count += node.process_count;
+            parent.cpu_usage += node.cpu_usage;
+            parent.memory += node.memory;
+            parent.children.push(node);
+        }
+    }
+    let mut root = nodes.remove("/").unwrap_or_default();
+    root.pids.sort();
+    root.children.sort_by(|a, b| a.path.cmp(&b.path));
+    root
+}
+
+pub fn parse_limit(content: &str) -> Option<Limit> {
+    match content.trim() {
+        "max" => Some(Limit::Max),
+        v => v.parse().ok().map(Limit::Value),
+    }
+}
+
+pub fn parse_cpu_max(content: &str) -> Option<CpuMax> {
+    let mut fields = content.split_whitespace();
+    let quota = parse_limit(fields.next()?)?;
+    let period = fields.next()?.parse().ok()?;
+    Some(CpuMax { quota, period })
+}
+
+pub fn parse_cpu_stat(content: &str) -> CpuStat {
+    let values: HashMap<&str, u64> = content.lines()
+        .filter_map(|line| line.split_once(' '))
+        .filter_map(|(k, v)| v.trim().parse().ok().map(|v| (k, v)))
+        .collect();
+    let nr_periods = values.get("nr_periods").copied();
+    let nr_throttled = values.get("nr_throttled").copied();
+    let throttled_percent = match (nr_periods, nr_throttled) {
+        (Some(periods), Some(throttled)) if periods > 0 => Some(throttled as f32 * 100.0 / periods as f32),
+        _ => None,
+    };
+    CpuStat {
+        usage_usec: values.get("usage_usec").copied(),
+        user_usec: values.get("user_usec").copied(),
+        system_usec: values.get("system_usec").copied(),
+        nr_periods,
+        nr_throttled,
+        throttled_usec: values.get("throttled_usec").copied(),
+        throttled_percent,
+    }
+}
+
+pub fn parse_pressure(content: &str) -> Pressure {
+    let mut pressure = Pressure::default();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut parsed = PressureLine::default();
+        for field in fields {
+            match field.split_once('=') {
+                Some(("avg10", v)) => parsed.avg10 = v.parse().unwrap_or_default(),
+                Some(("avg60", v)) => parsed.avg60 = v.parse().unwrap_or_default(),
+                Some(("avg300", v)) => parsed.avg300 = v.parse().unwrap_or_default(),
+                Some(("total", v)) => parsed.total = v.parse().unwrap_or_default(),
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => pressure.some = Some(parsed),
+            Some("full") => pressure.full = Some(parsed),
+            _ => {}
+        }
+    }
+    pressure
+}
+
+/// The cgroup v2 mount; hybrid systems put it under `unified`.
+fn unified_mount() -> PathBuf {
+    let mount = Path::new(CGROUP_MOUNT);
+    if mount.join("cgroup.controllers").exists() {
+        mount.to_path_buf()
+    } else {
+        mount.join("unified")
+    }
+}
+
+fn group_dir(path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(ApiError::InvalidArgument("Invalid cgroup path".into()).with_path(path));
+    }
+    let dir = unified_mount().join(relative);
+    if !dir.is_dir() {
+        return Err(ApiError::NotFound("No such cgroup".into()).with_path(path));
+    }
+    Ok(dir)
+}
+
+pub fn stats(path: &str) -> Result<CgroupStats> {
+    let dir = group_dir(path)?;
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    Ok(CgroupStats {
+        path: path.to_string(),
+        memory_current: read("memory.current").and_then(|v| v.trim().parse().ok()),
+        memory_max: read("memory.max").and_then(|v| parse_limit(&v)),
+        memory_high: read("memory.high").and_then(|v| parse_limit(&v)),
+        cpu_max: read("cpu.max").and_then(|v| parse_cpu_max(&v)),
+        cpu_stat: read("cpu.stat").map(|v| parse_cpu_stat(&v)),
+        pids_current: read("pids.current").and_then(|v| v.trim().parse().ok()),
+        pids_max: read("pids.max").and_then(|v| parse_limit(&v)),
+        cpu_pressure: read("cpu.pressure").map(|v| parse_pressure(&v)),
+        memory_pressure: read("memory.pressure").map(|v| parse_pressure(&v)),
+        io_pressure: read("io.pressure").map(|v| parse_pressure(&v)),
+    })
+}
+
+/// The tightest `memory.max` between `path` and the root, since any ancestor can cap the group.
+pub fn memory_limit(path: &str) -> Option<u64> {
+    let mut limit: Option<u64> = None;
+    let mut group = Some(path);
+    while let Some(path) = group {
+        let max = group_dir(path).ok()
+            .and_then(|dir| fs::read_to_string(dir.join("memory.max")).ok())
+            .and_then(|v| parse_limit(&v));
+        if let Some(Limit::Value(max)) = max {
+            limit = Some(limit.map_or(max, |limit| limit.min(max)));
+        }
+        group = parent_path(path);
+    }
+    limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "4f2a6c1e9b3d8a7f0c5e2b1d4a9f8e7c6b5a4d3c2b1a0f9e8d7c6b5a4f3e2d1c";
+
+    #[test]
+    fn test_parse_path() {
+        let info = parse_path(&format!("/system.slice/docker-{}.scope", ID));
+        assert_eq!(info.container_id.as_deref(), Some(ID));
+        assert_eq!(info.runtime, Some(ContainerRuntime::Docker));
+        assert_eq!(info.slice.as_deref(), Some("system.slice"));
+
+        let info = parse_path(&format!("/kubepods.slice/kubepods-pod1.slice/cri-containerd-{}.scope", ID));
+        assert_eq!(info.runtime, Some(ContainerRuntime::Containerd));
+        assert_eq!(info.slice.as_deref(), Some("kubepods-pod1.slice"));
+
+        let info = parse_path(&format!("/machine.slice/libpod-conmon-{}.scope", ID));
+        assert_eq!(info.container_id, None);
+        assert_eq!(info.unit.as_deref(), Some(&*format!("libpod-conmon-{}.scope", ID)));
+
+        let info = parse_path(&format!("/docker/{}", ID));
+        assert_eq!(info.runtime, Some(ContainerRuntime::Docker));
+
+        let info = parse_path("/user.slice/user-1000.slice/user@1000.service/app.slice/foo.service");
+        assert_eq!(info.container_id, None);
+        assert_eq!(info.unit.as_deref(), Some("foo.service"));
+        assert_eq!(info.slice.as_deref(), Some("app.slice"));
+    }
+
+    #[test]
+    fn test_parse_proc_cgroup() {
+        assert_eq!(parse_proc_cgroup("0::/init.scope\n").as_deref(), Some("/init.scope"));
+        assert_eq!(parse_proc_cgroup("12:pids:/\n0::/a/b\n").as_deref(), Some("/a/b"));
+        assert_eq!(parse_proc_cgroup("1:name=systemd:/\n"), None);
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let process = |pid: u32, path: &str, memory: u64| ProcessInfo {
+            pid,
+            memory: Some(memory),
+            cgroup: Some(parse_path(path)),
+            ..ProcessInfo::default()
+        };
+        let root = hierarchy(&[
+            process(1, "/init.scope", 10),
+            process(2, "/system.slice/a.service", 20),
+            process(3, "/system.slice/b.service", 30),
+            process(4, "/system.slice/b.service", 40),
+        ]);
+        assert_eq!(root.process_count, 4);
+        assert_eq!(root.memory, 100);
+        let system = root.children.iter().find(|c| c.path == "/system.slice").unwrap();
+        assert!(system.pids.is_empty());
+        assert_eq!(system.memory, 90);
+        assert_eq!(system.children[1].pids, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(parse_limit("max\n"), Some(Limit::Max));
+        assert_eq!(parse_limit("536870912\n"), Some(Limit::Value(536870912)));
+        assert_eq!(parse_cpu_max("50000 100000\n"), Some(CpuMax { quota: Limit::Value(50000), period: 100000 }));
+        assert_eq!(parse_cpu_max("max 100000\n").map(|c| c.quota), Some(Limit::Max));
+    }
+
+    #[test]
+    fn test_parse_cpu_stat_and_pressure() {
+        let stat = parse_cpu_stat("usage_usec 900\nuser_usec 600\nsystem_usec 300\nnr_periods 200\nnr_throttled 50\nthrottled_usec 7000\n");
+        assert_eq!(stat.usage_usec, Some(900));
+        assert_eq!(stat.throttled_usec, Some(7000));
+        assert_eq!(stat.throttled_percent, Some(25.0));
+
+        let pressure = parse_pressure("some avg10=1.50 avg60=0.75 avg300=0.10 total=12345\nfull avg10=0.00 avg60=0.00 avg300=0.00 total=0\n");
+        assert_eq!(pressure.some.as_ref().map(|p| p.avg10), Some(1.5));
+        assert_eq!(pressure.some.map(|p| p.total), Some(12345));
+        assert_eq!(pressure.full.map(|p| p.avg60), Some(0.0));
+    }
+
+    #[test]
+    fn test_group_dir_rejects_traversal() {
+        assert!(group_dir("/../etc").is_err());
+        assert!(group_dir("/a/../../etc").is_err());
+    }
+}
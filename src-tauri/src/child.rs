@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::{oneshot, watch, Mutex, RwLock};
+
+use crate::AppState;
+use crate::error::{ApiError, ErrorDetail, Result};
+use crate::monitor::SharedExitReports;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SpawnRequest {
+    pub cmd: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ChildStatus {
+    Running,
+    Exited { exit_code: Option<i32> },
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct SpawnedChild {
+    pub pid: u32,
+    pub cmd: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub status: ChildStatus,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub enum ChildStream {
+    Stdout,
+    Stderr,
+}
+
+/// Payload of the `child-output` event, one per line.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ChildOutput {
+    pub pid: u32,
+    pub stream: ChildStream,
+    pub line: String,
+}
+
+/// Payload of the `child-exited` event.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ChildExit {
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+}
+
+/// How long an exited child stays listed when nobody waits for it.
+const EXITED_RETENTION: Duration = Duration::from_secs(60);
+
+struct ChildHandle {
+    request: SpawnRequest,
+    /// Locked on its own, so a child that stops reading can't stall the whole manager.
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+    kill_tx: Option<oneshot::Sender<()>>,
+    status_rx: watch::Receiver<ChildStatus>,
+}
+
+impl ChildHandle {
+    fn spawned_child(&self, pid: u32) -> SpawnedChild {
+        SpawnedChild {
+            pid,
+            cmd: self.request.cmd.clone(),
+            args: self.request.args.clone(),
+            cwd: self.request.cwd.clone(),
+            status: self.status_rx.borrow().clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ChildManager {
+    children: HashMap<u32, ChildHandle>,
+}
+
+impl ChildManager {
+    pub fn list(&self) -> Vec<SpawnedChild> {
+        self.children.iter().map(|(pid, handle)| handle.spawned_child(*pid)).collect()
+    }
+
+    fn get_mut(&mut self, pid: u32) -> Result<&mut ChildHandle> {
+        self.children.get_mut(&pid).ok_or(ApiError::ProcessNotFound("No spawned child".into()).with_pid(pid))
+    }
+}
+
+async fn stream_lines<R: AsyncRead + Unpin>(app_state: Arc<RwLock<AppState>>, pid: u32, stream: ChildStream, reader: R) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf).trim_end_matches(['\r', '\n']).to_string();
+                app_state.read().await.emit("child-output", ChildOutput { pid, stream: stream.clone(), line });
+            }
+        }
+    }
+}
+
+pub async fn spawn(app_state: &Arc<RwLock<AppState>>, request: SpawnRequest) -> Result<SpawnedChild> {
+    let mut command = Command::new(&request.cmd);
+    command
+        .args(&request.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = request.cwd.as_ref() {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = request.env.as_ref() {
+        command.envs(env);
+    }
+    let mut child = command.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ApiError::InvalidArgument(Box::new(ErrorDetail::new("Program or working directory not found").caused_by(&e)))
+            .with_path(&request.cmd),
+        _ => ApiError::from(e),
+    })?;
+    let pid = child.id().ok_or(ApiError::Error("Child exited immediately".into()))?;
+
+    let (children, monitor) = {
+        let state = app_state.read().await;
+        (state.children.clone(), state.monitor.clone())
+    };
+    let exit_reports: SharedExitReports = {
+        let mut monitor = monitor.lock().await;
+        monitor.mark_spawned(pid);
+        monitor.exit_reports()
+    };
+
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(stream_lines(app_state.clone(), pid, ChildStream::Stdout, stdout));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(stream_lines(app_state.clone(), pid, ChildStream::Stderr, stderr));
+    }
+
+    let (kill_tx, kill_rx) = oneshot::channel::<()>();
+    let (status_tx, status_rx) = watch::channel(ChildStatus::Running);
+    let handle = ChildHandle {
+        request,
+        stdin: Arc::new(Mutex::new(child.stdin.take())),
+        kill_tx: Some(kill_tx),
+        status_rx,
+    };
+    let spawned = handle.spawned_child(pid);
+    children.lock().await.children.insert(pid, handle);
+
+    let app_state = app_state.clone();
+    tokio::spawn(async move {
+        let status = tokio::select! {
+            status = child.wait() => status,
+            _ = kill_rx => {
+                let _ = child.start_kill();
+                child.wait().await
+            }
+        };
+        let exit_code = status.ok().and_then(|s| s.code());
+        if let Some(code) = exit_code {
+            exit_reports.lock().unwrap().codes.insert(pid, code);
+        }
+        let _ = status_tx.send(ChildStatus::Exited { exit_code });
+        app_state.read().await.emit("child-exited", ChildExit { pid, exit_code });
+
+        tokio::time::sleep(EXITED_RETENTION).await;
+        let mut children = children.lock().await;
+        // the pid may already belong to a newer child
+        if children.children.get(&pid).is_some_and(|handle| handle.status_rx.same_channel(&status_tx.subscribe())) {
+            children.children.remove(&pid);
+        }
+    });
+
+    Ok(spawned)
+}
+
+pub async fn write_stdin(app_state: &Arc<RwLock<AppState>>, pid: u32, data: String) -> Result<()> {
+    let children = app_state.read().await.children.clone();
+    let stdin = children.lock().await.get_mut(pid)?.stdin.clone();
+    let mut stdin = stdin.lock().await;
+    let stdin = stdin.as_mut().ok_or(ApiError::Error("stdin is closed".into()).with_pid(pid))?;
+    stdin.write_all(data.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+pub async fn kill(app_state: &Arc<RwLock<AppState>>, pid: u32) -> Result<()> {
+    let children = app_state.read().await.children.clone();
+    let mut children = children.lock().await;
+    let handle = children.get_mut(pid)?;
+    if let Some(kill_tx) = handle.kill_tx.take() {
+        let _ = kill_tx.send(());
+    }
+    // a blocked writer holds the lock; it fails once the child is gone
+    if let Ok(mut stdin) = handle.stdin.try_lock() {
+        *stdin = None;
+    }
+    Ok(())
+}
+
+pub async fn wait(app_state: &Arc<RwLock<AppState>>, pid: u32) -> Result<ChildStatus> {
+    let children = app_state.read().await.children.clone();
+    let mut status_rx = children.lock().await.get_mut(pid)?.status_rx.clone();
+    let status = status_rx
+        .wait_for(|status| *status != ChildStatus::Running)
+        .await
+        .map_err(|e| ApiError::TokioError(e.to_string().into()).with_pid(pid))?
+        .clone();
+    // the exit has been observed, so the entry can go
+    let mut children = children.lock().await;
+    if children.children.get(&pid).is_some_and(|handle| handle.status_rx.same_channel(&status_rx)) {
+        children.children.remove(&pid);
+    }
+    Ok(status)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> SpawnRequest {
+        SpawnRequest { cmd: "sh".to_string(), args: vec!["-c".to_string(), script.to_string()], ..SpawnRequest::default() }
+    }
+
+    #[tokio::test]
+    async fn test_stdin_and_exit_code() {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let spawned = spawn(&app_state, shell("read x; echo $x; exit 3")).await.unwrap();
+        write_stdin(&app_state, spawned.pid, "hello\n".to_string()).await.unwrap();
+        let status = tokio::time::timeout(Duration::from_secs(10), wait(&app_state, spawned.pid)).await.unwrap().unwrap();
+        assert_eq!(status, ChildStatus::Exited { exit_code: Some(3) });
+        // the observed exit drops the entry
+        assert!(app_state.read().await.children.lock().await.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_kill() {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let spawned = spawn(&app_state, SpawnRequest { cmd: "sleep".to_string(), args: vec!["60".to_string()], ..SpawnRequest::default() }).await.unwrap();
+        kill(&app_state, spawned.pid).await.unwrap();
+        // killed by a signal, so there is no exit code
+        let status = tokio::time::timeout(Duration::from_secs(10), wait(&app_state, spawned.pid)).await.unwrap().unwrap();
+        assert_eq!(status, ChildStatus::Exited { exit_code: None });
+    }
+
+    #[tokio::test]
+    async fn test_unknown_pid() {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let pid = u32::MAX;
+        assert!(matches!(write_stdin(&app_state, pid, String::new()).await, Err(ApiError::ProcessNotFound(_))));
+        assert!(matches!(kill(&app_state, pid).await, Err(ApiError::ProcessNotFound(_))));
+        assert!(matches!(wait(&app_state, pid).await, Err(ApiError::ProcessNotFound(_))));
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use tokio::sync::RwLock;
+use tracing::Instrument;
+
+use crate::AppState;
+use crate::error::Result;
+use crate::http_server::{self, ServInfo};
+use crate::{leak, logging, monitor, remote, supervisor};
+
+/// `tr-process --daemon`: the collector and the HTTP/WebSocket API without a window.
+#[derive(Parser, Debug)]
+#[command(name = "tr-process", version, about = "Run tr-process headless")]
+pub struct DaemonArgs {
+    #[arg(long)]
+    pub daemon: bool,
+    #[arg(long, default_value = "127.0.0.1")]
+    pub ip: String,
+    /// 0 picks a free port.
+    #[arg(long, default_value_t = 0)]
+    pub port: u16,
+    /// Bearer token required by the API.
+    #[arg(long, env = "TR_PROCESS_TOKEN")]
+    pub token: Option<String>,
+}
+
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == "--daemon")
+}
+
+pub async fn run(args: DaemonArgs) -> Result<()> {
+    logging::init();
+    let mut state = AppState::new();
+    state.api_token = args.token;
+    let app_state = Arc::new(RwLock::new(state));
+
+    if let Err(e) = supervisor::run(app_state.clone()).await {
+        tracing::error!("supervisor error: {}", e);
+    }
+    if let Err(e) = remote::run(app_state.clone()).await {
+        tracing::error!("remote error: {}", e);
+    }
+    tokio::spawn(monitor::run_sampler(app_state.clone()).instrument(tracing::info_span!("sampler")));
+    tokio::spawn(leak::run(app_state.clone()).instrument(tracing::info_span!("leak")));
+    tokio::spawn(logging::run(app_state.clone()));
+
+    let handle = http_server::run(app_state.clone(), ServInfo {
+        name: "tr-process".to_string(),
+        ip: args.ip,
+        port: args.port,
+        path: String::new(),
+    }).await?;
+    tracing::info!("listening on http://{}:{}", handle.serv_info.ip, handle.serv_info.port);
+    {
+        let mut state = app_state.write().await;
+        state.serv_info = Some(handle.serv_info.clone());
+        state.shutdown_tx = handle.shutdown_tx.clone();
+    }
+
+    tokio::signal::ctrl_c().await?;
+    if let Some(tx) = handle.shutdown_tx.lock().await.take() {
+        let _ = tx.send(handle.serv_info);
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::overview::rate;
+
+/// `/proc/diskstats` counts in 512-byte sectors regardless of the device's block size.
+const SECTOR_SIZE: u64 = 512;
+
+/// Syscall counters from `/proc/<pid>/io`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessIo {
+    pub syscr: u64,
+    pub syscw: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+/// One row of `/proc/diskstats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiskStat {
+    pub name: String,
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub in_flight: u64,
+    /// Milliseconds the device had I/O in flight.
+    pub io_ticks: u64,
+    /// `io_ticks` weighted by the number of requests in flight.
+    pub weighted_io_ticks: u64,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct DeviceIo {
+    pub name: String,
+    pub total_read_bytes: u64,
+    pub total_write_bytes: u64,
+    /// Requests currently in flight.
+    pub in_flight: u64,
+    /// Rates over the last sampler tick; unset until two samples were taken.
+    pub read_bytes_per_sec: Option<f64>,
+    pub write_bytes_per_sec: Option<f64>,
+    pub reads_per_sec: Option<f64>,
+    pub writes_per_sec: Option<f64>,
+    /// Average number of requests in flight over the tick.
+    pub queue_depth: Option<f64>,
+    /// Percentage of the tick the device was busy.
+    pub utilization: Option<f64>,
+}
+
+pub fn parse_proc_io(content: &str) -> Option<ProcessIo> {
+    let values: HashMap<&str, u64> = content.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim(), value.trim().parse().ok()?))
+        })
+        .collect();
+    Some(ProcessIo {
+        syscr: *values.get("syscr")?,
+        syscw: *values.get("syscw")?,
+        cancelled_write_bytes: values.get("cancelled_write_bytes").copied().unwrap_or_default(),
+    })
+}
+
+/// `/proc/<pid>/io` is only readable for our own processes unless we run as root.
+#[cfg(target_os = "linux")]
+pub fn read_process_io(pid: u32) -> Option<ProcessIo> {
+    parse_proc_io(&std::fs::read_to_string(crate::procfs::proc_path(pid, "io")).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_io(_pid: u32) -> Option<ProcessIo> {
+    None
+}
+
+pub fn parse_diskstats(content: &str) -> Vec<DiskStat> {
+    content.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let num = |i: usize| fields.get(i).and_then(|f| f.parse::<u64>().ok());
+        Some(DiskStat {
+            name: fields.get(2)?.to_string(),
+            reads: num(3)?,
+            sectors_read: num(5)?,
+            writes: num(7)?,
+            sectors_written: num(9)?,
+            in_flight: num(11)?,
+            io_ticks: num(12)?,
+            weighted_io_ticks: num(13)?,
+        })
+    })
+    // devices that never saw any I/O, e.g. unused loop devices
+    .filter(|stat| stat.reads > 0 || stat.writes > 0)
+    .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_diskstats() -> Vec<DiskStat> {
+    std::fs::read_to_string("/proc/diskstats").map(|c| parse_diskstats(&c)).unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_diskstats() -> Vec<DiskStat> {
+    vec![]
+}
+
+/// Per-device throughput between two `/proc/diskstats` samples taken `elapsed` apart.
+pub fn device_io(prev: &[DiskStat], cur: &[DiskStat], elapsed: Option<Duration>) -> Vec<DeviceIo> {
+    let prev: HashMap<&str, &DiskStat> = prev.iter().map(|s| (s.name.as_str(), s)).collect();
+    cur.iter().map(|stat| {
+        let before = prev.get(stat.name.as_str());
+        // counters reset when a device is detached and re-attached under the same name
+        let delta = |f: fn(&DiskStat) -> u64| before.and_then(|b| f(stat).checked_sub(f(b)));
+        let per_sec = |f: fn(&DiskStat) -> u64| delta(f).and_then(|d| rate(d, elapsed));
+        let elapsed_ms = elapsed.map(|e| e.as_secs_f64() * 1000.0).filter(|ms| *ms > 0.0);
+        let per_ms = |f: fn(&DiskStat) -> u64| Some(delta(f)? as f64 / elapsed_ms?);
+        DeviceIo {
+            name: stat.name.clone(),
+            total_read_bytes: stat.sectors_read * SECTOR_SIZE,
+            total_write_bytes: stat.sectors_written * SECTOR_SIZE,
+            in_flight: stat.in_flight,
+            read_bytes_per_sec: per_sec(|s| s.sectors_read * SECTOR_SIZE),
+            write_bytes_per_sec: per_sec(|s| s.sectors_written * SECTOR_SIZE),
+            reads_per_sec: per_sec(|s| s.reads),
+            writes_per_sec: per_sec(|s| s.writes),
+            queue_depth: per_ms(|s| s.weighted_io_ticks),
+            utilization: per_ms(|s| s.io_ticks).map(|u| (u * 100.0).min(100.0)),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_io() {
+        let content = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\nread_bytes: 0\nwrite_bytes: 323932160\ncancelled_write_bytes: 4096\n";
+        assert_eq!(parse_proc_io(content), Some(ProcessIo { syscr: 632687, syscw: 632675, cancelled_write_bytes: 4096 }));
+        assert_eq!(parse_proc_io("rchar: 1\n"), None);
+    }
+
+    #[test]
+    fn test_device_io() {
+        let content = "\
+ 253       0 vda 1000 10 8000 500 2000 20 16000 900 0 1200 1400 0 0 0 0 0 0
+   7       0 loop0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+";
+        let prev = parse_diskstats(content);
+        assert_eq!(prev.len(), 1);
+        let cur = vec![DiskStat { reads: 1100, sectors_read: 10000, writes: 2400, in_flight: 3, io_ticks: 2200, weighted_io_ticks: 3400, ..prev[0].clone() }];
+        let io = device_io(&prev, &cur, Some(Duration::from_secs(2)));
+        assert_eq!(io[0].reads_per_sec, Some(50.0));
+        assert_eq!(io[0].writes_per_sec, Some(200.0));
+        assert_eq!(io[0].read_bytes_per_sec, Some(2000.0 * 512.0 / 2.0));
+        assert_eq!(io[0].queue_depth, Some(1.0));
+        assert_eq!(io[0].utilization, Some(50.0));
+        assert_eq!(device_io(&[], &cur, Some(Duration::from_secs(2)))[0].reads_per_sec, None);
+    }
+}
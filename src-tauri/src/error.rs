@@ -1,58 +1,342 @@
+use std::fmt;
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use thiserror::Error;
 use specta::Type;
 use serde::{Serialize, Deserialize};
+use serde_with::skip_serializing_none;
 pub type Result<T> = std::result::Result<T, ApiError>;
 
-#[derive(Type, Serialize, Deserialize, Error, Debug)]
+/// Serialized with the variant name as `code`, so both the HTTP API and Tauri commands
+/// hand the frontend `{ code, message, ... }` to match on.
+#[derive(Type, Serialize, Deserialize, Error, Debug, Clone)]
 #[derive(PartialEq)]
+#[serde(tag = "code")]
 pub enum ApiError {
     #[error("Error: {0}")]
-    Error(String),
+    Error(Box<ErrorDetail>),
 
     #[error("JSON error: {0}")]
-    JsonError(String),
+    JsonError(Box<ErrorDetail>),
 
     #[error("Tokio error: {0}")]
-    TokioError(String),
+    TokioError(Box<ErrorDetail>),
 
     #[error("IoError: {0}")]
-    IoError(String),
+    IoError(Box<ErrorDetail>),
 
 
     #[error("NetstatError: {0}")]
-    NetstatError(String),
+    NetstatError(Box<ErrorDetail>),
 
     #[error("WindowsError: {0}")]
-    WindowsError(String),
+    WindowsError(Box<ErrorDetail>),
+
+    #[error("HttpError: {0}")]
+    HttpError(Box<ErrorDetail>),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(Box<ErrorDetail>),
+
+    #[error("Process not found: {0}")]
+    ProcessNotFound(Box<ErrorDetail>),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(Box<ErrorDetail>),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(Box<ErrorDetail>),
+
+    #[error("Not found: {0}")]
+    NotFound(Box<ErrorDetail>),
+
+    #[error("Conflict: {0}")]
+    Conflict(Box<ErrorDetail>),
+
+    #[error("Unavailable: {0}")]
+    Unavailable(Box<ErrorDetail>),
+
+    #[error("Timeout: {0}")]
+    Timeout(Box<ErrorDetail>),
+
+    #[error("Address in use: {0}")]
+    AddressInUse(Box<ErrorDetail>),
+
+    #[error("Unsupported: {0}")]
+    Unsupported(Box<ErrorDetail>),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(Box<ErrorDetail>),
+
+}
+
+#[skip_serializing_none]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ErrorDetail {
+    pub message: String,
+    /// What was being attempted, e.g. `kill` or `bind`.
+    pub operation: Option<String>,
+    pub pid: Option<u32>,
+    pub path: Option<String>,
+    pub port: Option<u16>,
+    /// errno on Unix, HRESULT on Windows.
+    pub os_error: Option<i32>,
+    /// Underlying errors, outermost first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<String>,
+}
+
+impl ErrorDetail {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), ..Self::default() }
+    }
+
+    /// Records `e`'s message as the cause, followed by its own sources.
+    pub fn caused_by(mut self, e: &dyn std::error::Error) -> Self {
+        let mut cause = Some(e);
+        while let Some(e) = cause {
+            self.causes.push(e.to_string());
+            cause = e.source();
+        }
+        self
+    }
+
+    /// The message of a wrapped error, with whatever it wraps as causes.
+    fn from_error(e: &dyn std::error::Error) -> Self {
+        let detail = Self::new(e.to_string());
+        match e.source() {
+            Some(source) => detail.caused_by(source),
+            None => detail,
+        }
+    }
+}
+
+// boxed in `ApiError` so that `Result`s stay small
+impl From<String> for Box<ErrorDetail> {
+    fn from(message: String) -> Self {
+        Box::new(ErrorDetail::new(message))
+    }
+}
+
+impl From<&str> for Box<ErrorDetail> {
+    fn from(message: &str) -> Self {
+        Box::new(ErrorDetail::new(message))
+    }
+}
+
+impl fmt::Display for ErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        let context: Vec<String> = [
+            self.operation.as_ref().map(|op| op.to_string()),
+            self.pid.map(|pid| format!("pid {}", pid)),
+            self.path.as_ref().map(|path| format!("path {}", path)),
+            self.port.map(|port| format!("port {}", port)),
+        ].into_iter().flatten().collect();
+        if !context.is_empty() {
+            write!(f, " ({})", context.join(", "))?;
+        }
+        for cause in &self.causes {
+            write!(f, ": {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl ApiError {
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            ApiError::Error(d) | ApiError::JsonError(d) | ApiError::TokioError(d) | ApiError::IoError(d)
+            | ApiError::NetstatError(d) | ApiError::WindowsError(d) | ApiError::HttpError(d)
+            | ApiError::PermissionDenied(d) | ApiError::ProcessNotFound(d) | ApiError::InvalidArgument(d)
+            | ApiError::Unauthorized(d) | ApiError::NotFound(d) | ApiError::Conflict(d)
+            | ApiError::Unavailable(d) | ApiError::Timeout(d) | ApiError::AddressInUse(d)
+            | ApiError::Unsupported(d) | ApiError::TooManyRequests(d) => d,
+        }
+    }
+
+    fn detail_mut(&mut self) -> &mut ErrorDetail {
+        match self {
+            ApiError::Error(d) | ApiError::JsonError(d) | ApiError::TokioError(d) | ApiError::IoError(d)
+            | ApiError::NetstatError(d) | ApiError::WindowsError(d) | ApiError::HttpError(d)
+            | ApiError::PermissionDenied(d) | ApiError::ProcessNotFound(d) | ApiError::InvalidArgument(d)
+            | ApiError::Unauthorized(d) | ApiError::NotFound(d) | ApiError::Conflict(d)
+            | ApiError::Unavailable(d) | ApiError::Timeout(d) | ApiError::AddressInUse(d)
+            | ApiError::Unsupported(d) | ApiError::TooManyRequests(d) => d,
+        }
+    }
 
+    pub fn with_operation(mut self, operation: impl Into<String>) -> Self {
+        self.detail_mut().operation = Some(operation.into());
+        self
+    }
+
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.detail_mut().pid = Some(pid);
+        self
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.detail_mut().path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.detail_mut().port = Some(port);
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            // malformed request bodies become `InvalidArgument` at the extractor; a `JsonError` is our own config or serialization
+            ApiError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            ApiError::ProcessNotFound(_) | ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) | ApiError::AddressInUse(_) => StatusCode::CONFLICT,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            // upstream failures of a remote host
+            ApiError::HttpError(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ApiError {
+    /// Maps a failed syscall or `/proc` write on `pid` to the matching variant.
+    pub fn from_os_error(err: std::io::Error, pid: u32) -> Self {
+        let errno = err.raw_os_error();
+        let err = match errno {
+            Some(libc::ESRCH) | Some(libc::ENOENT) => ApiError::ProcessNotFound("No such process".into()),
+            Some(libc::EINVAL) => ApiError::InvalidArgument(err.to_string().into()),
+            _ => ApiError::from(err),
+        };
+        let mut err = err.with_pid(pid);
+        err.detail_mut().os_error = errno;
+        err
+    }
+
+    pub fn last_os_error(pid: u32) -> Self {
+        Self::from_os_error(std::io::Error::last_os_error(), pid)
+    }
 }
 
 impl From<serde_json::error::Error> for ApiError {
     fn from(e: serde_json::error::Error) -> Self {
-        ApiError::JsonError(e.to_string())
+        ApiError::JsonError(ErrorDetail::from_error(&e).into())
     }
 }
 
 impl From<tokio::sync::oneshot::error::RecvError> for ApiError {
     fn from(e: tokio::sync::oneshot::error::RecvError) -> Self {
-        ApiError::TokioError(e.to_string())
+        ApiError::TokioError(ErrorDetail::from_error(&e).into())
+    }
+}
+
+impl From<tokio::task::JoinError> for ApiError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        ApiError::TokioError(ErrorDetail::from_error(&e).into())
     }
 }
 
 impl From<std::io::Error> for ApiError {
     fn from(e: std::io::Error) -> Self {
-        ApiError::IoError(e.to_string())
+        let mut detail = Box::new(ErrorDetail::from_error(&e));
+        detail.os_error = e.raw_os_error();
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied(detail),
+            std::io::ErrorKind::AddrInUse => ApiError::AddressInUse(detail),
+            std::io::ErrorKind::TimedOut => ApiError::Timeout(detail),
+            std::io::ErrorKind::Unsupported => ApiError::Unsupported(detail),
+            _ => ApiError::IoError(detail),
+        }
     }
 }
 
 impl From<netstat2::error::Error> for ApiError {
     fn from(e: netstat2::error::Error) -> Self {
-        ApiError::NetstatError(e.to_string())
+        ApiError::NetstatError(e.to_string().into())
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        let detail = Box::new(ErrorDetail::from_error(&e));
+        if e.is_timeout() {
+            ApiError::Timeout(detail)
+        } else {
+            ApiError::HttpError(detail)
+        }
+    }
+}
+
+impl From<axum::extract::rejection::JsonRejection> for ApiError {
+    fn from(e: axum::extract::rejection::JsonRejection) -> Self {
+        ApiError::InvalidArgument(e.body_text().into())
+    }
+}
+
+impl From<axum::extract::rejection::QueryRejection> for ApiError {
+    fn from(e: axum::extract::rejection::QueryRejection) -> Self {
+        ApiError::InvalidArgument(e.body_text().into())
     }
 }
 
+#[cfg(windows)]
 impl From<windows::core::Error> for ApiError {
     fn from(e: windows::core::Error) -> Self {
-        ApiError::WindowsError(e.to_string())
+        let mut detail = Box::new(ErrorDetail::new(e.message()));
+        detail.os_error = Some(e.code().0);
+        ApiError::WindowsError(detail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_shape() {
+        let err = ApiError::NotFound("No service named web".into()).with_operation("start");
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "NotFound", "message": "No service named web", "operation": "start" }));
+        assert_eq!(serde_json::from_value::<ApiError>(json).unwrap(), err);
+        assert_eq!(err.to_string(), "Not found: No service named web (start)");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_io_error_kinds() {
+        let err = ApiError::from(std::io::Error::from_raw_os_error(libc::EADDRINUSE)).with_port(80);
+        assert!(matches!(err, ApiError::AddressInUse(_)));
+        assert_eq!(err.detail().os_error, Some(libc::EADDRINUSE));
+        assert_eq!(err.detail().port, Some(80));
+        assert!(matches!(ApiError::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied)), ApiError::PermissionDenied(_)));
+        // a missing file on our side is not the client's 404
+        let err = ApiError::from(std::io::Error::from_raw_os_error(libc::ENOENT));
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let err = ApiError::from(serde_json::from_str::<u32>("{").unwrap_err());
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_from_os_error() {
+        let err = ApiError::from_os_error(std::io::Error::from_raw_os_error(libc::EPERM), 42);
+        assert!(matches!(err, ApiError::PermissionDenied(_)));
+        assert_eq!(err.detail().pid, Some(42));
+        assert_eq!(err.detail().os_error, Some(libc::EPERM));
     }
 }
@@ -0,0 +1,284 @@
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::Result;
+use crate::monitor::{ProcessExit, SAMPLE_INTERVAL};
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "exec_events.json";
+pub const RECENT_CAPACITY: usize = 500;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExecConfig {
+    /// Off by default: without `CAP_NET_ADMIN` the fallback rescans `/proc` many times a second.
+    pub enabled: bool,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExecSource {
+    /// Fork/exec/exit events from the Linux netlink process connector.
+    ProcConnector,
+    /// `/proc` scanning, used when the connector needs privileges we don't have.
+    Polling,
+    Disabled,
+    Unsupported,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ExecParent {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ExecEvent {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: Option<String>,
+    pub cmdline: Option<String>,
+    pub exe: Option<String>,
+    /// Nearest parent first, up to init.
+    pub parents: Vec<ExecParent>,
+    /// Milliseconds since the unix epoch.
+    pub started_at: u64,
+    pub exited_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// Lifetime in milliseconds.
+    pub duration: Option<u64>,
+}
+
+impl From<&ExecEvent> for ProcessExit {
+    fn from(event: &ExecEvent) -> Self {
+        Self {
+            pid: event.pid,
+            ppid: event.ppid,
+            name: event.name.clone(),
+            exe: event.exe.clone(),
+            cpu_usage: None,
+            memory: None,
+            accumulated_cpu_time: None,
+            runtime: event.duration.map(|d| d / 1000),
+            exit_code: event.exit_code,
+            exited_at: event.exited_at.unwrap_or(event.started_at) / 1000,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ExecEvents {
+    pub source: ExecSource,
+    pub events: Vec<ExecEvent>,
+}
+
+/// What we could read about a process when it started or exec'd.
+#[derive(Default)]
+pub struct ExecDetails {
+    pub name: Option<String>,
+    pub cmdline: Option<String>,
+    pub exe: Option<String>,
+    pub parents: Vec<ExecParent>,
+}
+
+pub struct ExecTracker {
+    config: ExecConfig,
+    /// Whether a source thread is alive; it exits on its own once the config disables it.
+    running: bool,
+    source: ExecSource,
+    live: HashMap<u32, ExecEvent>,
+    recent: VecDeque<ExecEvent>,
+}
+
+pub type SharedExecTracker = Arc<std::sync::Mutex<ExecTracker>>;
+
+impl Default for ExecTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecTracker {
+    pub fn new() -> Self {
+        Self {
+            config: ExecConfig::default(),
+            running: false,
+            source: if cfg!(target_os = "linux") { ExecSource::Disabled } else { ExecSource::Unsupported },
+            live: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn set_source(&mut self, source: ExecSource) {
+        self.source = source;
+    }
+
+    pub fn config(&self) -> ExecConfig {
+        self.config.clone()
+    }
+
+    /// Returns true when a source thread has to be started for the new config.
+    pub fn set_config(&mut self, config: ExecConfig) -> bool {
+        let start = config.enabled && !self.running;
+        self.running |= start;
+        self.config = config;
+        start
+    }
+
+    /// Checked by the source thread between reads; once disabled it records the stop and should return.
+    pub fn keep_running(&mut self) -> bool {
+        if !self.config.enabled {
+            self.running = false;
+            self.source = ExecSource::Disabled;
+            self.live.clear();
+        }
+        self.config.enabled
+    }
+
+    /// Forgets processes whose exit we missed, e.g. after the connector dropped events.
+    pub fn prune(&mut self, pids: &HashSet<u32>) {
+        self.live.retain(|pid, _| pids.contains(pid));
+    }
+
+    pub fn started(&mut self, pid: u32, ppid: Option<u32>, details: ExecDetails, at: u64) {
+        self.live.insert(pid, ExecEvent {
+            pid,
+            ppid,
+            name: details.name,
+            cmdline: details.cmdline,
+            exe: details.exe,
+            parents: details.parents,
+            started_at: at,
+            exited_at: None,
+            exit_code: None,
+            duration: None,
+        });
+    }
+
+    pub fn exec(&mut self, pid: u32, details: ExecDetails, at: u64) {
+        match self.live.get_mut(&pid) {
+            Some(event) => {
+                event.name = details.name.or(event.name.take());
+                event.cmdline = details.cmdline;
+                event.exe = details.exe;
+                if !details.parents.is_empty() {
+                    event.ppid = details.parents.first().map(|p| p.pid);
+                    event.parents = details.parents;
+                }
+            }
+            None => {
+                let ppid = details.parents.first().map(|p| p.pid);
+                self.started(pid, ppid, details, at);
+            }
+        }
+    }
+
+    /// Finishes tracking `pid`; returns the event when it lived shorter than a sampler interval.
+    pub fn exited(&mut self, pid: u32, exit_code: Option<i32>, at: u64) -> Option<ExecEvent> {
+        let mut event = self.live.remove(&pid)?;
+        let duration = at.saturating_sub(event.started_at);
+        if duration >= SAMPLE_INTERVAL.as_millis() as u64 {
+            return None;
+        }
+        event.exited_at = Some(at);
+        event.exit_code = exit_code;
+        event.duration = Some(duration);
+        if self.recent.len() >= RECENT_CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(event.clone());
+        Some(event)
+    }
+
+    pub fn events(&self) -> ExecEvents {
+        ExecEvents {
+            source: self.source.clone(),
+            events: self.recent.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<ExecConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(ExecConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &ExecConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_lived_exec() {
+        let mut tracker = ExecTracker::new();
+        tracker.started(10, Some(1), ExecDetails::default(), 1_000);
+        tracker.exec(10, ExecDetails { name: Some("cc".to_string()), ..ExecDetails::default() }, 1_010);
+        let event = tracker.exited(10, Some(0), 1_500).unwrap();
+        assert_eq!(event.name.as_deref(), Some("cc"));
+        assert_eq!(event.duration, Some(500));
+        assert_eq!(tracker.events().events.len(), 1);
+    }
+
+    #[test]
+    fn test_long_lived_exec_is_not_recent() {
+        let mut tracker = ExecTracker::new();
+        tracker.started(10, Some(1), ExecDetails::default(), 1_000);
+        assert!(tracker.exited(10, Some(0), 60_000).is_none());
+        assert!(tracker.exited(11, None, 60_000).is_none());
+        assert!(tracker.events().events.is_empty());
+    }
+
+    #[test]
+    fn test_config_starts_and_stops_source() {
+        let mut tracker = ExecTracker::new();
+        assert!(tracker.set_config(ExecConfig { enabled: true }));
+        // already running
+        assert!(!tracker.set_config(ExecConfig { enabled: true }));
+        tracker.started(10, Some(1), ExecDetails::default(), 1_000);
+        tracker.started(11, Some(1), ExecDetails::default(), 1_000);
+        tracker.prune(&HashSet::from([11]));
+        assert_eq!(tracker.live.len(), 1);
+        assert!(tracker.keep_running());
+        assert!(!tracker.set_config(ExecConfig { enabled: false }));
+        assert!(!tracker.keep_running());
+        assert!(tracker.live.is_empty());
+        assert!(tracker.set_config(ExecConfig { enabled: true }));
+    }
+}
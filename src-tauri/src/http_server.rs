@@ -1,9 +1,17 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::absolute;
 use std::sync::Arc;
-use tokio::sync::{oneshot, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
 use axum::{Router};
 use axum::{Json, body::Bytes, response::{IntoResponse}};
 use axum::body::Body;
+use axum::extract::{ConnectInfo, Request};
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::middleware::{self, Next};
 use axum::response::Response;
 use axum::routing::{get, post};
 use http::{header, HeaderValue, StatusCode};
@@ -11,14 +19,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use serde_with::{serde_as, skip_serializing_none};
 use tower_http::services::ServeDir;
-use tower_http::cors::{CorsLayer, Any};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::Instrument;
 use specta::Type;
 
-use tauri::Emitter;
-
 use crate::AppState;
-use crate::error::Result;
+use crate::audit::AuditEntry;
+use crate::cgroup;
+use crate::error::{ApiError, Result};
+use crate::integrity;
+use crate::logging::{self, LogQuery};
+use crate::metrics;
+use crate::namespace::{self, NamespaceKind};
+use crate::supervisor;
 use crate::utils::get_resource_path;
+use crate::wait::{self, WaitRequest};
 
 #[derive(Clone)]
 pub struct HttpServerHandle {
@@ -38,78 +53,183 @@ pub struct ServInfo {
 }
 
 
-#[allow(dead_code)]
+/// An emitted event as sent to `/api/v1/ws` clients.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AppEvent {
+    pub event: String,
+    pub payload: Value,
+}
+
+/// Version of the `HttpNotify`/`HttpReply` protocol spoken by this build.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// How long `/emit` waits for the window to answer a command.
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ViewKind {
+    /// Table, tree and graph side by side.
+    Split,
+    Tree,
+    Table,
+    Graph,
+}
+
+#[skip_serializing_none]
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
 pub enum HttpCmd {
     Refresh,
+    SelectPid { pid: u32 },
+    /// Selects the process owning a socket on the local port.
+    FocusPort { port: u16 },
+    /// Filters the table by name, pid or port; `None` clears it.
+    ApplyFilter { text: Option<String> },
+    SwitchView { view: ViewKind },
+    /// Replies with the processes and selection the window is showing.
+    ExportSnapshot,
+    KillProcess { pid: u32 },
+}
+
+impl HttpCmd {
+    /// Commands that change the system rather than the view; refused unless the server has a token.
+    pub fn is_mutating(&self) -> bool {
+        matches!(self, HttpCmd::KillProcess { .. })
+    }
+}
+
+/// Refuses mutating commands and routes unless the server has a token; with one set,
+/// `require_token` has already checked it.
+fn require_configured_token(state: &AppState, what: &str) -> Result<()> {
+    if state.api_token.is_none() {
+        let error = format!("{} is only accepted when the server runs with a token", what);
+        return Err(ApiError::PermissionDenied(error.into()));
+    }
+    Ok(())
 }
 
-#[allow(dead_code)]
 #[skip_serializing_none]
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
 pub struct HttpNotify {
+    /// Protocol version of the sender; 1 when unset.
+    pub version: Option<u32>,
+    /// Correlation id echoed in the reply; assigned by `/emit` when unset.
+    pub id: Option<String>,
     pub cmd: HttpCmd,
-    pub param: Option<String>,
 }
 
+/// The window's answer to an `HttpNotify`.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct HttpReply {
+    pub id: String,
+    pub version: u32,
+    pub ok: bool,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Commands sent to the window that are still waiting for an `HttpReply`, by id.
+pub type PendingReplies = Arc<std::sync::Mutex<HashMap<String, oneshot::Sender<HttpReply>>>>;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
 pub async fn run(app_state: Arc<RwLock<AppState>>, serv_info: ServInfo) -> Result<HttpServerHandle> {
-    println!("run: {:?}", serv_info);
-    // create_mem()?;
-    // println!("create_mem");
+    tracing::debug!(?serv_info, "starting http server");
     let resource = get_resource_path()?;
     let new_serv_info = ServInfo {
         path: resource.to_string_lossy().to_string(),
         ..serv_info
     };
+    let loopback = new_serv_info.ip == "localhost"
+        || new_serv_info.ip.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if !loopback && app_state.read().await.api_token.is_none() {
+        let error = format!("Refusing to listen on {} without a token; set --token or bind to a loopback address", new_serv_info.ip);
+        return Err(ApiError::InvalidArgument(error.into()).with_port(new_serv_info.port));
+    }
     let (tx, rx) = oneshot::channel();
-    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<ServInfo>();
 
     let shared_shutdown_tx = Arc::new(Mutex::new(Some(shutdown_tx)));
     tokio::spawn(async move {
 
         let serv_path = absolute(new_serv_info.path).unwrap();
-        println!("serv_path: {}", &serv_path.to_string_lossy().to_string());
+        tracing::debug!(path = %serv_path.display(), "serving static files");
         let abs = serv_path.to_string_lossy().to_string();
         let resource = abs.clone();
-        let index_path = format!("{}/index.html", &abs);
-        println!("index_path: {}", &index_path);
+
+        let listener = match tokio::net::TcpListener::bind(format!("{}:{}", new_serv_info.ip, new_serv_info.port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = tx.send(Err(ApiError::from(e).with_operation("bind").with_port(new_serv_info.port)));
+                return;
+            }
+        };
+        let addr = listener.local_addr().unwrap();
 
         let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods([http::Method::GET, http::Method::HEAD, http::Method::POST, http::Method::PUT, http::Method::DELETE]);
+            .allow_origin(AllowOrigin::list(allowed_origins(addr)))
+            .allow_methods([http::Method::GET, http::Method::HEAD, http::Method::POST, http::Method::PUT, http::Method::DELETE])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
         let serv_dir = ServeDir::new(resource);
+        let emit = Router::new()
+            .route("/emit_jstr", post(post_emit_jstr))
+            .route("/emit", post(post_emit))
+            .route_layer(middleware::from_fn_with_state(app_state.clone(), rate_limit));
+        let api = Router::new()
+            .merge(emit)
+            .route("/metrics", get(get_metrics))
+            .route("/api/v1/processes", get(get_processes))
+            .route("/api/v1/sockets", get(get_sockets))
+            .route("/api/v1/cgroups", get(get_cgroups))
+            .route("/api/v1/cgroups/stats", get(get_cgroup_stats))
+            .route("/api/v1/namespaces", get(get_namespaces))
+            .route("/api/v1/system", get(get_system))
+            .route("/api/v1/disk_io", get(get_disk_io))
+            .route("/api/v1/integrity", get(get_integrity))
+            .route("/api/v1/anomalies", get(get_anomalies))
+            .route("/api/v1/leaks", get(get_leaks))
+            .route("/api/v1/logs", get(get_logs))
+            .route("/api/v1/audit", get(get_audit))
+            .route("/api/v1/wait", post(post_wait))
+            .route("/api/v1/exits", get(get_exits))
+            .route("/api/v1/exec_events", get(get_exec_events))
+            .route("/api/v1/ws", get(get_ws))
+            .route("/api/v1/remotes", get(get_remotes))
+            .route("/api/v1/supervisor", get(get_supervisor))
+            .route("/api/v1/supervisor/{name}/start", post(post_service_start))
+            .route("/api/v1/supervisor/{name}/stop", post(post_service_stop))
+            .route_layer(middleware::from_fn_with_state(app_state.clone(), require_token))
+            // outermost, so rejected requests are audited too
+            .route_layer(middleware::from_fn_with_state(app_state.clone(), audit_request))
+            .with_state(app_state.clone());
         let app = Router::new()
             .route("/serv_info", get(get_serv_info)).with_state(app_state.clone())
-            .route("/emit_jstr", post(post_emit_jstr)).with_state(app_state.clone())
-            .route("/emit", post(post_emit)).with_state(app_state.clone())
-            // .route("/", get(move || async move {
-            //     axum::response::Html(html)
-            // }))
+            .merge(api)
             .fallback_service(serv_dir)
+            .layer(middleware::from_fn(trace_request))
             .layer(cors)
             ;
 
-        let listener = tokio::net::TcpListener::bind(format!("{}:{}", new_serv_info.ip, new_serv_info.port)).await.unwrap();
-        let addr = listener.local_addr().unwrap();
-        let _ = tx.send(ServInfo { name: new_serv_info.name, ip: addr.ip().to_string(), port: addr.port(), path: abs.clone() });
-        axum::serve(listener, app)
+        let _ = tx.send(Ok(ServInfo { name: new_serv_info.name, ip: addr.ip().to_string(), port: addr.port(), path: abs.clone() }));
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
             .with_graceful_shutdown(async {
                 match shutdown_rx.await {
                     Ok(serv_info) => {
-                        println!("shutdown: {:?}", serv_info);
+                        tracing::info!(port = serv_info.port, "http server shut down");
                     },
                     Err(e) => {
-                        println!("shutdown: {:?}", e);
+                        tracing::warn!("http server shut down: {}", e);
                     },
                 }
             })
             .await
             .unwrap();
     });
-    let ret = rx.await?;
+    let ret = rx.await??;
     Ok(HttpServerHandle{
         serv_info: ret,
         shutdown_tx: shared_shutdown_tx
@@ -117,6 +237,18 @@ pub async fn run(app_state: Arc<RwLock<AppState>>, serv_info: ServInfo) -> Resul
 }
 
 
+/// Origins allowed to call the API from a browser: pages served by this server and the app's own webview.
+fn allowed_origins(addr: SocketAddr) -> Vec<HeaderValue> {
+    let port = addr.port();
+    [
+        format!("http://{}", addr),
+        format!("http://localhost:{}", port),
+        format!("http://127.0.0.1:{}", port),
+        "tauri://localhost".to_string(),
+        "http://tauri.localhost".to_string(),
+    ].iter().filter_map(|origin| HeaderValue::from_str(origin).ok()).collect()
+}
+
 async fn get_serv_info(
     axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>
 ) -> impl IntoResponse {
@@ -126,51 +258,465 @@ async fn get_serv_info(
             Json(serv_info).into_response()
         },
         None => {
-            let error_json: Value = json!({
-                "error": "No server info"
-            });
-            error_json.to_string().into_response()
+            ApiError::Unavailable("No server info".into()).into_response()
         }
     }
 }
 
+/// Same as `/emit`, for clients that can't set a JSON content type.
 async fn post_emit_jstr(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>, body: Bytes) -> impl IntoResponse {
+    tracing::debug!(len = body.len(), "emit_jstr");
+    match serde_json::from_slice::<HttpNotify>(&body) {
+        Ok(payload) => emit_notify(&app_state, payload).await,
+        Err(e) => ApiError::InvalidArgument(format!("Invalid HttpNotify: {}", e).into()).into_response(),
+    }
+}
+
+/// Forwards a command to the window and answers with its `HttpReply`.
+async fn post_emit(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    payload: std::result::Result<Json<HttpNotify>, JsonRejection>,
+) -> impl IntoResponse {
+    match payload {
+        Ok(Json(payload)) => emit_notify(&app_state, payload).await,
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// Without a window, as in daemon mode, the command only reaches WebSocket clients, so it is
+/// acknowledged with 202 instead of waiting for a reply.
+async fn emit_notify(app_state: &Arc<RwLock<AppState>>, payload: HttpNotify) -> Response {
+    let id = payload.id.clone().unwrap_or_else(|| format!("cmd-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+    if payload.version.unwrap_or(1) > PROTOCOL_VERSION {
+        let error = format!("Unsupported protocol version {:?}, this build speaks {}", payload.version, PROTOCOL_VERSION);
+        return ApiError::InvalidArgument(error.into()).into_response();
+    }
+
     let state = app_state.read().await;
-    let body = match String::from_utf8(body.to_vec()) {
-        Ok(s) => s,
-        Err(_) => return Json(json!({ "error": "Invalid UTF-8" })).into_response(),
+    if payload.cmd.is_mutating() {
+        if let Err(e) = require_configured_token(&state, &format!("{:?}", payload.cmd)) {
+            return e.into_response();
+        }
+    }
+    let notify = HttpNotify { version: Some(PROTOCOL_VERSION), id: Some(id.clone()), ..payload };
+    if state.window.is_none() {
+        state.emit("http", notify);
+        let ack = HttpReply { id, version: PROTOCOL_VERSION, ok: true, result: None, error: None };
+        return (StatusCode::ACCEPTED, Json(ack)).into_response();
+    }
+    let (tx, rx) = oneshot::channel();
+    {
+        let mut pending = state.pending_replies.lock().unwrap();
+        if pending.contains_key(&id) {
+            return ApiError::Conflict(format!("Command {} is already pending", id).into()).into_response();
+        }
+        pending.insert(id.clone(), tx);
+    }
+    state.emit("http", notify);
+    let pending = state.pending_replies.clone();
+    drop(state);
+
+    match tokio::time::timeout(REPLY_TIMEOUT, rx).await {
+        Ok(Ok(reply)) => Json(reply).into_response(),
+        _ => {
+            pending.lock().unwrap().remove(&id);
+            ApiError::Timeout(format!("The window did not reply to {} in time", id).into()).into_response()
+        }
+    }
+}
+
+/// Hands the window's reply to the `/emit` request waiting for it.
+pub fn resolve_reply(pending: &PendingReplies, reply: HttpReply) -> Result<()> {
+    let tx = pending.lock().unwrap().remove(&reply.id)
+        .ok_or_else(|| ApiError::NotFound(format!("No pending command {}", reply.id).into()))?;
+    // the request may have timed out meanwhile
+    let _ = tx.send(reply);
+    Ok(())
+}
+
+/// Logs each request with its status and latency inside a `request` span.
+async fn trace_request(req: Request, next: Next) -> Response {
+    let span = tracing::debug_span!("request", method = %req.method(), path = %req.uri().path());
+    let started = Instant::now();
+    let response = next.run(req).instrument(span.clone()).await;
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.in_scope(|| {
+        if response.status().is_server_error() {
+            tracing::warn!(status, latency_ms, "request failed");
+        } else {
+            tracing::debug!(status, latency_ms, "request handled");
+        }
+    });
+    response
+}
+
+/// Records each API request in the audit log.
+async fn audit_request(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let client = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip().to_string());
+    let method = req.method().to_string();
+    let route = req.uri().path().to_string();
+    let payload_size = req.headers().get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let response = next.run(req).await;
+    let entry = AuditEntry {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+        client,
+        method,
+        route,
+        payload_size,
+        status: response.status().as_u16(),
+        latency_ms: started.elapsed().as_millis() as u64,
     };
-    println!("notify: {}", body);
-    let window = match state.window.clone() {
-        Some(w) => w,
-        None => {
-            return Json(json!({
-                "error": "No window"
-            })).into_response()
+    let audit = app_state.read().await.audit.clone();
+    audit.lock().unwrap().record(entry);
+    response
+}
+
+/// Answers 429 with `Retry-After` once a client has used up its `/emit` budget.
+async fn rate_limit(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>().cloned() else {
+        return next.run(req).await;
+    };
+    let limiter = app_state.read().await.rate_limiter.clone();
+    let checked = limiter.lock().unwrap().check(addr.ip(), Instant::now());
+    match checked {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => {
+            tracing::debug!(client = %addr.ip(), path = %req.uri().path(), "rate limited");
+            let mut response = ApiError::TooManyRequests(format!("Rate limit exceeded for {}", addr.ip()).into()).into_response();
+            let secs = retry_after.as_secs_f64().ceil() as u64;
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+            response
         }
+    }
+}
+
+/// Rejects requests without the configured bearer token; browsers can't set headers on
+/// WebSocket upgrades, so `?token=` is accepted as well.
+async fn require_token(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = app_state.read().await.api_token.clone() else {
+        return next.run(req).await;
     };
-    if let Err(e) = window.emit("http", body.clone()) {
-        eprintln!("emit error: {}", e);
+    let bearer = req.headers().get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let query = req.uri().query().unwrap_or_default()
+        .split('&')
+        .find_map(|kv| kv.strip_prefix("token="));
+    let matches = |given: Option<&str>| given.is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()));
+    if matches(bearer) || matches(query) {
+        return next.run(req).await;
+    }
+    ApiError::Unauthorized("Missing or invalid token".into()).into_response()
+}
+
+/// Compares without returning early, so response timing doesn't reveal how much of the token matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
     }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn get_processes(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Json(processes).into_response()
+}
+
+async fn get_sockets(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let sockets = monitor.lock().await.sockets();
+    Json(sockets).into_response()
+}
+
+async fn get_cgroups(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Json(cgroup::hierarchy(&processes)).into_response()
+}
+
+#[derive(Deserialize)]
+struct CgroupQuery {
+    path: Option<String>,
+}
+
+async fn get_cgroup_stats(axum::extract::Query(query): axum::extract::Query<CgroupQuery>) -> impl IntoResponse {
+    match cgroup::stats(query.path.as_deref().unwrap_or("/")) {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct NamespaceQuery {
+    kind: NamespaceKind,
+}
+
+async fn get_namespaces(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    query: std::result::Result<axum::extract::Query<NamespaceQuery>, QueryRejection>,
+) -> impl IntoResponse {
+    let query = match query {
+        Ok(axum::extract::Query(query)) => query,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let monitor = app_state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Json(namespace::groups(&processes, query.kind)).into_response()
+}
+
+async fn get_system(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let overview = monitor.lock().await.overview();
+    Json(overview).into_response()
+}
+
+async fn get_disk_io(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let disk_io = monitor.lock().await.disk_io();
+    Json(disk_io).into_response()
+}
+
+async fn get_integrity(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Json(integrity::issues(processes)).into_response()
+}
+
+async fn get_anomalies(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let anomalies = monitor.lock().await.anomalies();
+    Json(anomalies).into_response()
+}
+
+async fn get_leaks(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let leaks = app_state.read().await.leaks.clone();
+    let suspects = leaks.lock().await.suspects();
+    Json(suspects).into_response()
+}
+
+async fn get_logs(query: std::result::Result<axum::extract::Query<LogQuery>, QueryRejection>) -> impl IntoResponse {
+    let query = match query {
+        Ok(axum::extract::Query(query)) => query,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    match logging::logger() {
+        Ok(logger) => Json(logger.query(&query)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_audit(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let audit = app_state.read().await.audit.clone();
+    let entries = audit.lock().unwrap().entries();
+    Json(entries).into_response()
+}
+
+/// Answers once the condition holds, or with `satisfied: false` when the timeout passes first.
+async fn post_wait(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    request: std::result::Result<Json<WaitRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let request = match request {
+        Ok(Json(request)) => request,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let monitor = app_state.read().await.monitor.clone();
+    Json(wait::wait_for(monitor, &request).await).into_response()
+}
+
+async fn get_exits(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let monitor = app_state.read().await.monitor.clone();
+    let exit_log = monitor.lock().await.exit_log();
+    Json(exit_log).into_response()
+}
+
+async fn get_exec_events(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let exec_tracker = app_state.read().await.exec_tracker.clone();
+    let events = exec_tracker.lock().unwrap().events();
+    Json(events).into_response()
+}
+
+async fn get_ws(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let rx = app_state.read().await.events.subscribe();
+    ws.on_upgrade(move |socket| forward_events(socket, rx))
+}
+
+async fn forward_events(mut socket: WebSocket, mut rx: broadcast::Receiver<AppEvent>) {
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("ws client lagged by {} events", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+async fn get_remotes(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let remotes = app_state.read().await.remotes.clone();
+    let states = remotes.lock().await.states();
+    Json(states).into_response()
+}
+
+async fn get_supervisor(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let supervisor = app_state.read().await.supervisor.clone();
+    let states = supervisor.lock().await.states();
+    Json(states).into_response()
+}
+
+async fn post_service_start(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_configured_token(&*app_state.read().await, &format!("Starting service {}", name)) {
+        return e.into_response();
+    }
+    match supervisor::start(&app_state, &name).await {
+        Ok(()) => Json(json!({ "name": name })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn post_service_stop(
+    axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> impl IntoResponse {
+    if let Err(e) = require_configured_token(&*app_state.read().await, &format!("Stopping service {}", name)) {
+        return e.into_response();
+    }
+    match supervisor::stop(&app_state, &name).await {
+        Ok(()) => Json(json!({ "name": name })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_metrics(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>) -> impl IntoResponse {
+    let (monitor, config) = {
+        let state = app_state.read().await;
+        (state.monitor.clone(), state.metrics_config.clone())
+    };
+    let body = {
+        let monitor = monitor.lock().await;
+        metrics::render(&config, &monitor.processes(), &monitor.totals())
+    };
     Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .header(header::CONTENT_TYPE, HeaderValue::from_static(metrics::CONTENT_TYPE))
         .body(Body::from(body))
         .unwrap()
 }
 
-async fn post_emit(axum::extract::State(app_state): axum::extract::State<Arc<RwLock<AppState>>>, Json(payload): Json<HttpNotify>) -> impl IntoResponse {
-    let state = app_state.read().await;
-    let window = match state.window.clone() {
-        Some(w) => w,
-        None => {
-            return Json(json!({
-                "error": "No window"
-            })).into_response()
-        }
-    };
-    if let Err(e) = window.emit("http", payload.clone()) {
-        eprintln!("emit error: {}", e);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_notify_wire_format() {
+        let notify: HttpNotify = serde_json::from_str(r#"{"cmd":{"SelectPid":{"pid":42}}}"#).unwrap();
+        assert!(matches!(notify.cmd, HttpCmd::SelectPid { pid: 42 }));
+        assert_eq!(notify.version, None);
+        let notify: HttpNotify = serde_json::from_str(r#"{"version":1,"id":"a","cmd":"Refresh"}"#).unwrap();
+        assert_eq!(notify.id.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_allowed_origins() {
+        let origins = allowed_origins("0.0.0.0:7777".parse().unwrap());
+        assert!(origins.contains(&HeaderValue::from_static("http://127.0.0.1:7777")));
+        assert!(origins.contains(&HeaderValue::from_static("tauri://localhost")));
+        assert!(!origins.contains(&HeaderValue::from_static("http://127.0.0.1:8080")));
+    }
+
+    #[test]
+    fn test_is_mutating() {
+        assert!(HttpCmd::KillProcess { pid: 1 }.is_mutating());
+        assert!(!HttpCmd::Refresh.is_mutating());
+        assert!(!HttpCmd::SelectPid { pid: 1 }.is_mutating());
+    }
+
+    #[tokio::test]
+    async fn test_emit_kill_needs_token() {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let notify = |cmd: HttpCmd| Ok(Json(HttpNotify { version: None, id: None, cmd }));
+        let response = post_emit(axum::extract::State(app_state.clone()), notify(HttpCmd::KillProcess { pid: 1 })).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        // read-only commands need no token; without a window they are only acknowledged
+        let response = post_emit(axum::extract::State(app_state), notify(HttpCmd::Refresh)).await.into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_services_need_token() {
+        let app_state = Arc::new(RwLock::new(AppState::new()));
+        let path = |name: &str| axum::extract::Path(name.to_string());
+        let response = post_service_start(axum::extract::State(app_state.clone()), path("web")).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = post_service_stop(axum::extract::State(app_state.clone()), path("web")).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // past the token check, the unknown service is what fails
+        app_state.write().await.api_token = Some("secret".to_string());
+        let response = post_service_stop(axum::extract::State(app_state), path("web")).await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
-    Json(payload).into_response()
-}
\ No newline at end of file
+
+    #[test]
+    fn test_resolve_reply() {
+        let pending = PendingReplies::default();
+        let (tx, mut rx) = oneshot::channel();
+        pending.lock().unwrap().insert("cmd-1".to_string(), tx);
+        let reply = HttpReply { id: "cmd-1".to_string(), version: PROTOCOL_VERSION, ok: true, result: Some(json!({"pid": 1})), error: None };
+        resolve_reply(&pending, reply.clone()).unwrap();
+        assert!(rx.try_recv().unwrap().ok);
+        // a second reply has nobody waiting for it
+        assert!(resolve_reply(&pending, reply).is_err());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+
+    #[test]
+    fn test_error_response() {
+        let response = ApiError::NotFound("No service named web".into()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(ApiError::Conflict("".into()).status(), StatusCode::CONFLICT);
+        assert_eq!(ApiError::IoError("".into()).status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_value(ApiError::Unauthorized("Missing or invalid token".into())).unwrap();
+        assert_eq!(body, json!({ "code": "Unauthorized", "message": "Missing or invalid token" }));
+    }
+}
@@ -0,0 +1,265 @@
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::Result;
+use crate::sys::ProcessInfo;
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "integrity.json";
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IntegrityConfig {
+    /// Hashing is off unless enabled; deleted and replaced binaries are flagged either way.
+    pub enabled: bool,
+    /// Hex SHA-256 digests of known-good executables. When non-empty, anything else is flagged.
+    pub allow: Vec<String>,
+    /// Hex SHA-256 digests that are always flagged.
+    pub deny: Vec<String>,
+}
+
+impl IntegrityConfig {
+    pub fn verdict(&self, sha256: &str) -> Option<ListVerdict> {
+        let listed = |list: &[String]| list.iter().any(|h| h.eq_ignore_ascii_case(sha256));
+        if listed(&self.deny) {
+            Some(ListVerdict::Denied)
+        } else if listed(&self.allow) {
+            Some(ListVerdict::Allowed)
+        } else if !self.allow.is_empty() {
+            Some(ListVerdict::Unlisted)
+        } else {
+            None
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExeState {
+    Intact,
+    /// The path no longer exists.
+    Deleted,
+    /// Another file now lives at the path, e.g. after a deploy.
+    Replaced,
+    /// The running file was written to after the process started.
+    Modified,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ListVerdict {
+    Allowed,
+    Denied,
+    Unlisted,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ExeIntegrity {
+    pub state: ExeState,
+    /// Digest of the image the process is running, which may differ from the file on disk.
+    pub sha256: Option<String>,
+    pub verdict: Option<ListVerdict>,
+}
+
+impl ExeIntegrity {
+    pub fn flagged(&self) -> bool {
+        self.state != ExeState::Intact || matches!(self.verdict, Some(ListVerdict::Denied | ListVerdict::Unlisted))
+    }
+}
+
+/// Identity of a file: device, inode, mtime and size.
+type FileKey = (u64, u64, i64, u64);
+
+/// `exe_deleted` is the kernel's ` (deleted)` marker on `/proc/<pid>/exe`; `start_time` is in Unix seconds.
+pub fn classify(exe_deleted: bool, running: FileKey, on_disk: Option<FileKey>, start_time: Option<u64>) -> ExeState {
+    let (dev, ino, mtime, _) = running;
+    match on_disk {
+        None => ExeState::Deleted,
+        Some((disk_dev, disk_ino, _, _)) if exe_deleted || (disk_dev, disk_ino) != (dev, ino) => ExeState::Replaced,
+        _ if start_time.is_some_and(|start| mtime > start as i64) => ExeState::Modified,
+        _ => ExeState::Intact,
+    }
+}
+
+#[derive(Default)]
+struct HashCache {
+    hashes: HashMap<FileKey, String>,
+    /// Images handed to the hasher and not finished yet.
+    queued: HashSet<FileKey>,
+}
+
+/// Hashes each distinct executable image once and checks running processes against it.
+/// Hashing runs on its own thread, so the first pass over a busy box doesn't hold up sampling;
+/// `sha256` stays unset until the digest is ready.
+#[derive(Default)]
+pub struct IntegrityChecker {
+    config: IntegrityConfig,
+    cache: Arc<Mutex<HashCache>>,
+    hasher: Option<mpsc::Sender<(FileKey, PathBuf)>>,
+}
+
+impl IntegrityChecker {
+    pub fn config(&self) -> IntegrityConfig {
+        self.config.clone()
+    }
+
+    pub fn set_config(&mut self, config: IntegrityConfig) {
+        if !config.enabled {
+            self.cache.lock().unwrap().hashes.clear();
+        }
+        self.config = config;
+    }
+
+    pub fn apply(&mut self, processes: &mut HashMap<u32, ProcessInfo>) {
+        let mut seen = HashSet::new();
+        for info in processes.values_mut() {
+            info.integrity = self.check(info, &mut seen);
+        }
+        self.cache.lock().unwrap().hashes.retain(|key, _| seen.contains(key));
+    }
+
+    fn hash_later(&mut self, key: FileKey, path: PathBuf) {
+        let cache = self.cache.clone();
+        let hasher = self.hasher.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel::<(FileKey, PathBuf)>();
+            // ends once the checker and its sender are dropped
+            std::thread::spawn(move || {
+                for (key, path) in rx {
+                    let hash = hash_file(&path);
+                    let mut cache = cache.lock().unwrap();
+                    cache.queued.remove(&key);
+                    // a process that exited first is retried with the next one running the image
+                    if let Some(hash) = hash {
+                        cache.hashes.insert(key, hash);
+                    }
+                }
+            });
+            tx
+        });
+        let _ = hasher.send((key, path));
+    }
+
+    #[cfg(target_os = "linux")]
+    fn check(&mut self, info: &ProcessInfo, seen: &mut HashSet<FileKey>) -> Option<ExeIntegrity> {
+        use std::os::unix::fs::MetadataExt;
+
+        let key = |m: &fs::Metadata| (m.dev(), m.ino(), m.mtime(), m.size());
+        let exe = crate::procfs::proc_path(info.pid, "exe");
+        let link = fs::read_link(&exe).ok()?.to_string_lossy().to_string();
+        let (path, exe_deleted) = match link.strip_suffix(" (deleted)") {
+            Some(path) => (path.to_string(), true),
+            None => (link, false),
+        };
+        // stat through /proc follows to the running image even after it was unlinked
+        let running = key(&fs::metadata(&exe).ok()?);
+        let on_disk = fs::metadata(&path).ok().map(|m| key(&m));
+        let state = classify(exe_deleted, running, on_disk, info.start_time);
+
+        let sha256 = if self.config.enabled {
+            seen.insert(running);
+            let mut cache = self.cache.lock().unwrap();
+            let hash = cache.hashes.get(&running).cloned();
+            let queue = hash.is_none() && cache.queued.insert(running);
+            drop(cache);
+            if queue {
+                self.hash_later(running, exe);
+            }
+            hash
+        } else {
+            None
+        };
+        let verdict = sha256.as_deref().and_then(|h| self.config.verdict(h));
+        Some(ExeIntegrity { state, sha256, verdict })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn check(&mut self, _info: &ProcessInfo, _seen: &mut HashSet<FileKey>) -> Option<ExeIntegrity> {
+        None
+    }
+}
+
+pub fn issues(processes: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+    let mut issues: Vec<ProcessInfo> = processes.into_iter()
+        .filter(|p| p.integrity.as_ref().is_some_and(|i| i.flagged()))
+        .collect();
+    issues.sort_by_key(|p| p.pid);
+    issues
+}
+
+pub fn hash_file(path: &std::path::Path) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<IntegrityConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(IntegrityConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &IntegrityConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let running = (1, 100, 1000, 10);
+        assert_eq!(classify(false, running, Some(running), Some(2000)), ExeState::Intact);
+        assert_eq!(classify(true, running, None, Some(2000)), ExeState::Deleted);
+        assert_eq!(classify(true, running, Some((1, 200, 3000, 10)), Some(2000)), ExeState::Replaced);
+        assert_eq!(classify(false, running, Some((1, 200, 3000, 10)), Some(2000)), ExeState::Replaced);
+        assert_eq!(classify(false, running, Some(running), Some(500)), ExeState::Modified);
+    }
+
+    #[test]
+    fn test_verdict() {
+        let mut config = IntegrityConfig { enabled: true, allow: vec![], deny: vec!["AB".to_string()] };
+        assert_eq!(config.verdict("ab"), Some(ListVerdict::Denied));
+        assert_eq!(config.verdict("cd"), None);
+        config.allow = vec!["cd".to_string()];
+        assert_eq!(config.verdict("cd"), Some(ListVerdict::Allowed));
+        assert_eq!(config.verdict("ef"), Some(ListVerdict::Unlisted));
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("tr-process-hash-{}", std::process::id()));
+        fs::write(&path, "abc").unwrap();
+        let hash = hash_file(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(hash.as_deref(), Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use tokio::sync::RwLock;
+
+use crate::AppState;
+use crate::cgroup;
+use crate::error::Result;
+use crate::limits::{self, Resource, RlimitValue};
+use crate::sys::ProcessInfo;
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "leak.json";
+/// Leaks are slow, so history is kept at a coarser step than the sampler's.
+pub const LEAK_INTERVAL: Duration = Duration::from_secs(30);
+/// Two hours at `LEAK_INTERVAL`.
+pub const HISTORY_CAPACITY: usize = 240;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct LeakConfig {
+    /// Processes younger than this are not judged.
+    pub min_age_secs: u64,
+    pub min_samples: u32,
+    /// Resident memory growth, in bytes per hour, above which a process is suspect.
+    pub memory_slope: f64,
+    /// Open file descriptor growth, per hour, above which a process is suspect.
+    pub fd_slope: f64,
+    /// Minimum r² of the linear fit, so that spiky but flat usage isn't flagged.
+    pub min_r_squared: f64,
+}
+
+impl Default for LeakConfig {
+    fn default() -> Self {
+        Self {
+            min_age_secs: 30 * 60,
+            min_samples: 20,
+            memory_slope: 10.0 * 1024.0 * 1024.0,
+            fd_slope: 20.0,
+            min_r_squared: 0.8,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LeakResource {
+    Memory,
+    Fds,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct LeakSuspect {
+    pub pid: u32,
+    pub name: Option<String>,
+    pub resource: LeakResource,
+    /// Growth per hour, in bytes for memory.
+    pub slope: f64,
+    pub r_squared: f64,
+    pub current: u64,
+    /// The cgroup `memory.max` or total memory, whichever is lower, for `Memory`; the soft `RLIMIT_NOFILE` for `Fds`.
+    pub limit: Option<u64>,
+    /// Projected seconds until `limit` is reached at the current slope.
+    pub seconds_to_limit: Option<u64>,
+    pub samples: u32,
+    pub window_secs: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    /// Change per second.
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+/// Least-squares line through `(seconds, value)` points.
+pub fn linear_fit(points: &[(f64, f64)]) -> Option<Fit> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    // a perfectly flat series fits perfectly but isn't growing
+    let r_squared = if syy == 0.0 { 0.0 } else { (sxy * sxy) / (sxx * syy) };
+    Some(Fit { slope, intercept: mean_y - slope * mean_x, r_squared })
+}
+
+struct Sample {
+    at: Instant,
+    memory: Option<u64>,
+    fds: Option<u64>,
+}
+
+struct History {
+    start_time: Option<u64>,
+    samples: VecDeque<Sample>,
+}
+
+#[derive(Default)]
+pub struct LeakAnalyzer {
+    config: LeakConfig,
+    history: HashMap<u32, History>,
+    suspects: Vec<LeakSuspect>,
+}
+
+impl LeakAnalyzer {
+    pub fn new(config: LeakConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> LeakConfig {
+        self.config.clone()
+    }
+
+    pub fn set_config(&mut self, config: LeakConfig) {
+        self.config = config;
+    }
+
+    pub fn suspects(&self) -> Vec<LeakSuspect> {
+        self.suspects.clone()
+    }
+
+    pub fn record(&mut self, processes: &[ProcessInfo], at: Instant) {
+        let live: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+        self.history.retain(|pid, _| live.contains(pid));
+        for p in processes.iter().filter(|p| !p.is_missing()) {
+            let history = self.history.entry(p.pid).or_insert_with(|| History { start_time: p.start_time, samples: VecDeque::new() });
+            if history.start_time != p.start_time {
+                *history = History { start_time: p.start_time, samples: VecDeque::new() };
+            }
+            if history.samples.len() >= HISTORY_CAPACITY {
+                history.samples.pop_front();
+            }
+            history.samples.push_back(Sample { at, memory: p.memory, fds: p.open_files });
+        }
+    }
+
+    /// Re-fits every history and returns the suspects that weren't flagged on the previous run.
+    pub fn analyze(&mut self, processes: &[ProcessInfo], total_memory: u64) -> Vec<LeakSuspect> {
+        let by_pid: HashMap<u32, &ProcessInfo> = processes.iter().map(|p| (p.pid, p)).collect();
+        let mut suspects = vec![];
+        for (pid, history) in self.history.iter() {
+            let Some(p) = by_pid.get(pid) else { continue };
+            if p.uptime.unwrap_or_default() < self.config.min_age_secs {
+                continue;
+            }
+            for resource in [LeakResource::Memory, LeakResource::Fds] {
+                if let Some(suspect) = self.judge(p, history, resource, total_memory) {
+                    suspects.push(suspect);
+                }
+            }
+        }
+        suspects.sort_by_key(|s| s.pid);
+
+        let flagged: HashSet<(u32, LeakResource)> = self.suspects.iter().map(|s| (s.pid, s.resource.clone())).collect();
+        let new = suspects.iter().filter(|s| !flagged.contains(&(s.pid, s.resource.clone()))).cloned().collect();
+        self.suspects = suspects;
+        new
+    }
+
+    fn judge(&self, p: &ProcessInfo, history: &History, resource: LeakResource, total_memory: u64) -> Option<LeakSuspect> {
+        let first = history.samples.front()?.at;
+        let points: Vec<(f64, f64)> = history.samples.iter()
+            .filter_map(|s| {
+                let value = match resource {
+                    LeakResource::Memory => s.memory,
+                    LeakResource::Fds => s.fds,
+                }?;
+                Some((s.at.duration_since(first).as_secs_f64(), value as f64))
+            })
+            .collect();
+        if points.len() < self.config.min_samples as usize {
+            return None;
+        }
+        let fit = linear_fit(&points)?;
+        let per_hour = fit.slope * 3600.0;
+        let threshold = match resource {
+            LeakResource::Memory => self.config.memory_slope,
+            LeakResource::Fds => self.config.fd_slope,
+        };
+        if per_hour < threshold || fit.r_squared < self.config.min_r_squared {
+            return None;
+        }
+
+        let (last_x, _) = *points.last()?;
+        let current = fit.intercept + fit.slope * last_x;
+        let limit = limit_of(p, &resource, total_memory);
+        let seconds_to_limit = limit.map(|limit| ((limit as f64 - current).max(0.0) / fit.slope) as u64);
+        Some(LeakSuspect {
+            pid: p.pid,
+            name: p.name.clone(),
+            resource,
+            slope: per_hour,
+            r_squared: fit.r_squared,
+            current: current.max(0.0) as u64,
+            limit,
+            seconds_to_limit,
+            samples: points.len() as u32,
+            window_secs: last_x as u64,
+        })
+    }
+}
+
+fn limit_of(p: &ProcessInfo, resource: &LeakResource, total_memory: u64) -> Option<u64> {
+    match resource {
+        // resident memory runs into the cgroup's memory.max; RLIMIT_AS only caps address space
+        LeakResource::Memory => {
+            let cgroup_max = p.cgroup.as_ref().and_then(|c| cgroup::memory_limit(&c.path));
+            Some(cgroup_max.map_or(total_memory, |max| max.min(total_memory)))
+        }
+        LeakResource::Fds => limits::get_limits(p.pid).ok()?.limits.into_iter()
+            .find(|l| l.resource.as_ref() == Some(&Resource::Nofile))
+            .and_then(|l| match l.soft {
+                RlimitValue::Value(v) => Some(v),
+                RlimitValue::Unlimited => None,
+            }),
+    }
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<LeakConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(LeakConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &LeakConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+/// Samples the monitor every `LEAK_INTERVAL` and emits a `leak-suspect` event for each new finding.
+pub async fn run(app_state: Arc<RwLock<AppState>>) {
+    let mut interval = tokio::time::interval(LEAK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let (monitor, leaks) = {
+            let state = app_state.read().await;
+            (state.monitor.clone(), state.leaks.clone())
+        };
+        let (processes, total_memory) = {
+            let monitor = monitor.lock().await;
+            (monitor.processes(), monitor.totals().total_memory)
+        };
+        let new = tokio::task::spawn_blocking(move || {
+            let mut leaks = leaks.blocking_lock();
+            leaks.record(&processes, Instant::now());
+            leaks.analyze(&processes, total_memory)
+        }).await;
+        match new {
+            Ok(new) => {
+                let state = app_state.read().await;
+                for suspect in new {
+                    state.emit("leak-suspect", suspect);
+                }
+            }
+            Err(e) => tracing::error!("leak analyzer error: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_fit() {
+        let fit = linear_fit(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]).unwrap();
+        assert_eq!(fit.slope, 2.0);
+        assert_eq!(fit.intercept, 1.0);
+        assert_eq!(fit.r_squared, 1.0);
+        assert_eq!(linear_fit(&[(0.0, 4.0), (1.0, 4.0)]).unwrap().r_squared, 0.0);
+        assert_eq!(linear_fit(&[(0.0, 4.0)]), None);
+    }
+
+    #[test]
+    fn test_analyze_flags_steady_growth() {
+        let config = LeakConfig { min_age_secs: 0, min_samples: 10, ..LeakConfig::default() };
+        let mut analyzer = LeakAnalyzer::new(config);
+        let start = Instant::now();
+        let process = |pid: u32, memory: u64, fds: u64| ProcessInfo {
+            pid,
+            name: Some(format!("p{}", pid)),
+            memory: Some(memory),
+            open_files: Some(fds),
+            start_time: Some(1),
+            uptime: Some(3600),
+            ..ProcessInfo::default()
+        };
+        let mut new = vec![];
+        for i in 0..20u64 {
+            // pid 1 grows 1 MiB per minute, pid 2 wobbles around a flat line
+            let processes = vec![
+                process(1, (100 + i) << 20, 10),
+                process(2, (200 << 20) + (i % 2) * (8 << 20), 10),
+            ];
+            analyzer.record(&processes, start + Duration::from_secs(i * 60));
+            new = analyzer.analyze(&processes, 1 << 40);
+        }
+        let suspects = analyzer.suspects();
+        assert_eq!(suspects.len(), 1);
+        assert_eq!(suspects[0].pid, 1);
+        assert_eq!(suspects[0].resource, LeakResource::Memory);
+        assert!((suspects[0].slope - 60.0 * 1024.0 * 1024.0).abs() < 1.0);
+        assert!(suspects[0].seconds_to_limit.is_some());
+        // already reported on an earlier run
+        assert!(new.is_empty());
+    }
+}
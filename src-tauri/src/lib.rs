@@ -1,23 +1,161 @@
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{Manager, State, Window};
+use serde::Serialize;
+use tauri::{Emitter, Manager, State, Window};
 use tauri_specta::{collect_commands, Builder};
-use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
+use tracing::Instrument;
 
+use crate::anomaly::ProcessAnomalies;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::cgroup::{CgroupNode, CgroupStats};
+use crate::child::{ChildExit, ChildManager, ChildOutput, ChildStatus, SpawnRequest, SpawnedChild};
+use crate::diskio::DeviceIo;
 use crate::error::{ApiError, Result};
-use crate::http_server::{ServInfo};
+use crate::exec_events::{ExecConfig, ExecEvents, ExecTracker, SharedExecTracker};
+use crate::http_server::{AppEvent, HttpNotify, HttpReply, PendingReplies, ServInfo};
+use crate::integrity::IntegrityConfig;
+use crate::leak::{LeakAnalyzer, LeakConfig, LeakSuspect};
+use crate::limits::{ProcessLimits, Resource, RlimitValue};
+use crate::logging::{LogEntry, LogLevel, LogQuery};
+use crate::metrics::MetricsConfig;
+use crate::monitor::{Monitor, ProcessExit};
+use crate::namespace::{NamespaceGroup, NamespaceKind};
+use crate::overview::SystemOverview;
+use crate::rate_limit::RateLimiter;
+use crate::remote::{RemoteHost, RemoteState, Remotes};
+use crate::sched::{IoPriority, SchedInfo};
+use crate::smaps::{MemoryDetail, SubtreeMemory};
+use crate::supervisor::{ServiceDef, ServiceState, Supervisor};
 use crate::sys::{ProcessInfo};
 
-mod sys;
-mod error;
+pub mod sys;
+mod anomaly;
+mod audit;
+mod cgroup;
+mod child;
+pub mod daemon;
+mod diskio;
+pub mod error;
+mod exec_events;
 mod http_server;
+mod integrity;
+mod leak;
+mod limits;
+pub mod logging;
+mod metrics;
+pub mod monitor;
+mod namespace;
+mod overview;
+#[cfg(target_os = "linux")]
+mod proc_connector;
+#[cfg(target_os = "linux")]
+mod procfs;
+mod rate_limit;
+mod remote;
+mod sched;
+mod smaps;
+mod supervisor;
 mod utils;
+pub mod wait;
+
+const EVENT_CAPACITY: usize = 1024;
 
 #[derive(Clone)]
-struct AppState {
+pub struct AppState {
     pub window: Option<Window>,
     pub serv_info: Option<ServInfo>,
     pub shutdown_tx: Arc<Mutex<Option<oneshot::Sender<ServInfo>>>>,
+    pub monitor: Arc<Mutex<Monitor>>,
+    pub exec_tracker: SharedExecTracker,
+    pub children: Arc<Mutex<ChildManager>>,
+    pub supervisor: Arc<Mutex<Supervisor>>,
+    pub metrics_config: MetricsConfig,
+    pub remotes: Arc<Mutex<Remotes>>,
+    pub leaks: Arc<Mutex<LeakAnalyzer>>,
+    /// `/emit` commands waiting for the window to reply.
+    pub pending_replies: PendingReplies,
+    /// Every emitted event, for WebSocket clients.
+    pub events: broadcast::Sender<AppEvent>,
+    /// Bearer token the HTTP API requires, if any.
+    pub api_token: Option<String>,
+    /// Recent HTTP API requests.
+    pub audit: Arc<std::sync::Mutex<AuditLog>>,
+    /// Throttles `/emit` per client so a runaway script can't flood the window.
+    pub rate_limiter: Arc<std::sync::Mutex<RateLimiter>>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppState {
+    /// Creates the state shared by the window and the daemon, and starts listening for process events.
+    pub fn new() -> Self {
+        let metrics_config = metrics::load_config().unwrap_or_else(|e| {
+            tracing::warn!("metrics config error: {}", e);
+            MetricsConfig::default()
+        });
+        let leak_config = leak::load_config().unwrap_or_else(|e| {
+            tracing::warn!("leak config error: {}", e);
+            LeakConfig::default()
+        });
+        let mut monitor = Monitor::new();
+        monitor.set_integrity_config(integrity::load_config().unwrap_or_else(|e| {
+            tracing::warn!("integrity config error: {}", e);
+            IntegrityConfig::default()
+        }));
+        let mut exec_tracker = ExecTracker::new();
+        let start_exec_source = exec_tracker.set_config(exec_events::load_config().unwrap_or_else(|e| {
+            tracing::warn!("exec events config error: {}", e);
+            ExecConfig::default()
+        }));
+        let exec_tracker: SharedExecTracker = Arc::new(std::sync::Mutex::new(exec_tracker));
+        #[cfg(target_os = "linux")]
+        if start_exec_source {
+            proc_connector::spawn(exec_tracker.clone(), monitor.exit_reports());
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = start_exec_source;
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+
+        Self {
+            window: None,
+            serv_info: None,
+            shutdown_tx: Arc::new(Mutex::new(None)),
+            monitor: Arc::new(Mutex::new(monitor)),
+            exec_tracker,
+            children: Arc::new(Mutex::new(ChildManager::default())),
+            supervisor: Arc::new(Mutex::new(Supervisor::default())),
+            metrics_config,
+            remotes: Arc::new(Mutex::new(Remotes::default())),
+            leaks: Arc::new(Mutex::new(LeakAnalyzer::new(leak_config))),
+            pending_replies: PendingReplies::default(),
+            events,
+            api_token: None,
+            audit: Arc::new(std::sync::Mutex::new(AuditLog::default())),
+            rate_limiter: Arc::new(std::sync::Mutex::new(RateLimiter::default())),
+        }
+    }
+
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if self.events.receiver_count() > 0 {
+            match serde_json::to_value(payload.clone()) {
+                Ok(payload) => {
+                    let _ = self.events.send(AppEvent { event: event.to_string(), payload });
+                }
+                Err(e) => tracing::warn!(target: logging::EMIT_TARGET, event, "emit error: {}", e),
+            }
+        }
+        if let Some(window) = self.window.clone() {
+            if let Err(e) = window.emit(event, payload) {
+                tracing::warn!(target: logging::EMIT_TARGET, event, "emit error: {}", e);
+            }
+        }
+    }
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -30,8 +168,361 @@ fn get_resource_path() -> Result<String> {
 
 #[tauri::command]
 #[specta::specta]
-fn get_process() -> Result<Vec<ProcessInfo>> {
-    sys::get_processes()
+async fn get_process(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<ProcessInfo>> {
+    monitor::refresh(state.inner()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_cgroups(state: State<'_, Arc<RwLock<AppState>>>) -> Result<CgroupNode> {
+    let monitor = state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Ok(cgroup::hierarchy(&processes))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_cgroup_stats(path: String) -> Result<CgroupStats> {
+    cgroup::stats(&path)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_namespaces(state: State<'_, Arc<RwLock<AppState>>>, kind: NamespaceKind) -> Result<Vec<NamespaceGroup>> {
+    let monitor = state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Ok(namespace::groups(&processes, kind))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_sched_info(pid: u32) -> Result<SchedInfo> {
+    sched::get_sched_info(pid)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_priority(pid: u32, nice: i32) -> Result<()> {
+    sched::set_priority(pid, nice)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_affinity(pid: u32) -> Result<Vec<u32>> {
+    sched::get_affinity(pid)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_affinity(pid: u32, cpus: Vec<u32>) -> Result<()> {
+    sched::set_affinity(pid, &cpus)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_io_priority(pid: u32, priority: IoPriority) -> Result<()> {
+    sched::set_io_priority(pid, &priority)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_limits(pid: u32) -> Result<ProcessLimits> {
+    limits::get_limits(pid)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_limit(pid: u32, resource: Resource, soft: RlimitValue, hard: RlimitValue) -> Result<()> {
+    limits::set_limit(pid, resource, soft, hard)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_oom_score_adj(pid: u32, value: i32) -> Result<()> {
+    limits::set_oom_score_adj(pid, value)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_memory_detail(pid: u32) -> Result<MemoryDetail> {
+    smaps::read(pid).ok_or(ApiError::NotFound("No memory breakdown".into()).with_pid(pid))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_subtree_memory(state: State<'_, Arc<RwLock<AppState>>>, pid: u32) -> Result<SubtreeMemory> {
+    let monitor = state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Ok(smaps::subtree(&processes, pid))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_system_overview(state: State<'_, Arc<RwLock<AppState>>>) -> Result<SystemOverview> {
+    let monitor = state.read().await.monitor.clone();
+    let overview = monitor.lock().await.overview();
+    Ok(overview)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_disk_io(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<DeviceIo>> {
+    let monitor = state.read().await.monitor.clone();
+    let disk_io = monitor.lock().await.disk_io();
+    Ok(disk_io)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_process_anomalies(state: State<'_, Arc<RwLock<AppState>>>) -> Result<ProcessAnomalies> {
+    let monitor = state.read().await.monitor.clone();
+    let anomalies = monitor.lock().await.anomalies();
+    Ok(anomalies)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_leak_suspects(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<LeakSuspect>> {
+    let leaks = state.read().await.leaks.clone();
+    let suspects = leaks.lock().await.suspects();
+    Ok(suspects)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_leak_config(state: State<'_, Arc<RwLock<AppState>>>) -> Result<LeakConfig> {
+    let leaks = state.read().await.leaks.clone();
+    let config = leaks.lock().await.config();
+    Ok(config)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_leak_config(state: State<'_, Arc<RwLock<AppState>>>, config: LeakConfig) -> Result<()> {
+    leak::save_config(&config)?;
+    let leaks = state.read().await.leaks.clone();
+    leaks.lock().await.set_config(config);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn reply_http(state: State<'_, Arc<RwLock<AppState>>>, reply: HttpReply) -> Result<()> {
+    let pending = state.read().await.pending_replies.clone();
+    http_server::resolve_reply(&pending, reply)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn kill_process(pid: u32) -> Result<()> {
+    sys::kill_process(pid)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_logs(level: Option<LogLevel>, since: Option<u64>, limit: Option<u32>) -> Result<Vec<LogEntry>> {
+    Ok(logging::logger()?.query(&LogQuery { level, since, limit }))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_audit_log(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<AuditEntry>> {
+    let audit = state.read().await.audit.clone();
+    let entries = audit.lock().unwrap().entries();
+    Ok(entries)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_log_level() -> Result<LogLevel> {
+    Ok(logging::logger()?.level())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_log_level(level: LogLevel) -> Result<()> {
+    logging::logger()?.set_level(level);
+    tracing::info!(%level, "log level changed");
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_exit_log(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<ProcessExit>> {
+    let monitor = state.read().await.monitor.clone();
+    let exit_log = monitor.lock().await.exit_log();
+    Ok(exit_log)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn exec_events(state: State<'_, Arc<RwLock<AppState>>>) -> Result<ExecEvents> {
+    let exec_tracker = state.read().await.exec_tracker.clone();
+    let events = exec_tracker.lock().unwrap().events();
+    Ok(events)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_exec_config(state: State<'_, Arc<RwLock<AppState>>>) -> Result<ExecConfig> {
+    let exec_tracker = state.read().await.exec_tracker.clone();
+    let config = exec_tracker.lock().unwrap().config();
+    Ok(config)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_exec_config(state: State<'_, Arc<RwLock<AppState>>>, config: ExecConfig) -> Result<()> {
+    exec_events::save_config(&config)?;
+    let (exec_tracker, monitor) = {
+        let state = state.read().await;
+        (state.exec_tracker.clone(), state.monitor.clone())
+    };
+    let start = exec_tracker.lock().unwrap().set_config(config);
+    #[cfg(target_os = "linux")]
+    if start {
+        let exit_reports = monitor.lock().await.exit_reports();
+        proc_connector::spawn(exec_tracker, exit_reports);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (start, monitor);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn spawn_process(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    cmd: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+) -> Result<SpawnedChild> {
+    child::spawn(state.inner(), SpawnRequest { cmd, args, cwd, env }).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn write_stdin(state: State<'_, Arc<RwLock<AppState>>>, pid: u32, data: String) -> Result<()> {
+    child::write_stdin(state.inner(), pid, data).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn kill_child(state: State<'_, Arc<RwLock<AppState>>>, pid: u32) -> Result<()> {
+    child::kill(state.inner(), pid).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn wait_child(state: State<'_, Arc<RwLock<AppState>>>, pid: u32) -> Result<ChildStatus> {
+    child::wait(state.inner(), pid).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_children(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<SpawnedChild>> {
+    let children = state.read().await.children.clone();
+    let list = children.lock().await.list();
+    Ok(list)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_services(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<ServiceState>> {
+    let supervisor = state.read().await.supervisor.clone();
+    let states = supervisor.lock().await.states();
+    Ok(states)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn add_service(state: State<'_, Arc<RwLock<AppState>>>, def: ServiceDef) -> Result<()> {
+    supervisor::add_service(state.inner(), def).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn remove_service(state: State<'_, Arc<RwLock<AppState>>>, name: String) -> Result<()> {
+    supervisor::remove_service(state.inner(), &name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn start_service(state: State<'_, Arc<RwLock<AppState>>>, name: String) -> Result<()> {
+    supervisor::start(state.inner(), &name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn stop_service(state: State<'_, Arc<RwLock<AppState>>>, name: String) -> Result<()> {
+    supervisor::stop(state.inner(), &name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_metrics_config(state: State<'_, Arc<RwLock<AppState>>>) -> Result<MetricsConfig> {
+    Ok(state.read().await.metrics_config.clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_metrics_config(state: State<'_, Arc<RwLock<AppState>>>, config: MetricsConfig) -> Result<()> {
+    metrics::save_config(&config)?;
+    state.write().await.metrics_config = config;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_integrity_config(state: State<'_, Arc<RwLock<AppState>>>) -> Result<IntegrityConfig> {
+    let monitor = state.read().await.monitor.clone();
+    let config = monitor.lock().await.integrity_config();
+    Ok(config)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_integrity_config(state: State<'_, Arc<RwLock<AppState>>>, config: IntegrityConfig) -> Result<()> {
+    integrity::save_config(&config)?;
+    let monitor = state.read().await.monitor.clone();
+    monitor.lock().await.set_integrity_config(config);
+    Ok(())
+}
+
+/// Processes whose executable was deleted, replaced or failed the allow/deny lists.
+#[tauri::command]
+#[specta::specta]
+async fn get_integrity_issues(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<ProcessInfo>> {
+    let monitor = state.read().await.monitor.clone();
+    let processes = monitor.lock().await.processes();
+    Ok(integrity::issues(processes))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_remotes(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<RemoteState>> {
+    let remotes = state.read().await.remotes.clone();
+    let states = remotes.lock().await.states();
+    Ok(states)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn add_remote(state: State<'_, Arc<RwLock<AppState>>>, host: RemoteHost) -> Result<()> {
+    remote::add_remote(state.inner(), host).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn remove_remote(state: State<'_, Arc<RwLock<AppState>>>, name: String) -> Result<()> {
+    remote::remove_remote(state.inner(), &name).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_all_processes(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Vec<ProcessInfo>> {
+    Ok(remote::merged_processes(state.inner()).await)
 }
 
 #[tauri::command]
@@ -39,14 +530,13 @@ fn get_process() -> Result<Vec<ProcessInfo>> {
 async fn run_http_server(state: State<'_, Arc<RwLock<AppState>>>, serv_info: ServInfo) -> Result<ServInfo> {
     let app_state = state.inner().clone();
     if app_state.read().await.serv_info.clone().is_some() {
-        println!("The server is already running.");
-        return Err(ApiError::Error("The server is already running.".to_string()))
+        return Err(ApiError::Conflict("The server is already running.".into()))
     };
 
     let app_state = state.inner().clone();
 
     let handle = http_server::run(app_state.clone(), serv_info).await?;
-    println!("{:?}", handle.serv_info.clone());
+    tracing::info!(ip = %handle.serv_info.ip, port = handle.serv_info.port, "http server started");
 
     let mut state = state.write().await;
 
@@ -57,49 +547,87 @@ async fn run_http_server(state: State<'_, Arc<RwLock<AppState>>>, serv_info: Ser
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub async fn run() {
+    logging::init();
 
     let builder = Builder::<tauri::Wry>::new().commands(collect_commands![
         get_resource_path,
         get_process,
+        get_cgroups,
+        get_cgroup_stats,
+        get_namespaces,
+        get_sched_info,
+        set_priority,
+        get_affinity,
+        set_affinity,
+        set_io_priority,
+        get_limits,
+        set_limit,
+        set_oom_score_adj,
+        get_memory_detail,
+        get_subtree_memory,
+        get_system_overview,
+        get_disk_io,
+        get_process_anomalies,
+        get_leak_suspects,
+        get_leak_config,
+        set_leak_config,
+        get_exit_log,
+        reply_http,
+        kill_process,
+        get_logs,
+        get_audit_log,
+        get_log_level,
+        set_log_level,
+        exec_events,
+        get_exec_config,
+        set_exec_config,
+        spawn_process,
+        write_stdin,
+        kill_child,
+        wait_child,
+        get_children,
+        get_services,
+        add_service,
+        remove_service,
+        start_service,
+        stop_service,
+        get_metrics_config,
+        set_metrics_config,
+        get_integrity_config,
+        set_integrity_config,
+        get_integrity_issues,
+        get_remotes,
+        add_remote,
+        remove_remote,
+        get_all_processes,
         run_http_server
-    ]);
+    ])
+        // event payloads
+        .typ::<HttpNotify>()
+        .typ::<ProcessExit>()
+        .typ::<ChildOutput>()
+        .typ::<ChildExit>()
+        .typ::<ServiceState>()
+        .typ::<RemoteState>()
+        .typ::<LeakSuspect>()
+        .typ::<LogEntry>();
 
     #[cfg(debug_assertions)]
     {
         use std::path::Path;
-        use std::fs::OpenOptions;
-        use std::io::Write;
 
         use specta_typescript::BigIntExportBehavior;
         use specta_typescript::Typescript;
-        use specta::TypeCollection;
-
-        use crate::http_server::{HttpNotify};
 
         let bindings_path = Path::new("../src/bindings.ts");
         let ts = Typescript::default().bigint(BigIntExportBehavior::Number);
         builder
-            .export(ts.clone(), bindings_path)
+            .export(ts, bindings_path)
             .expect("Failed to export typescript bindings");
-
-        let mut types = TypeCollection::default();
-        types.register::<HttpNotify>();
-        let http_notify_str = ts.clone().export(&types).unwrap();
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(bindings_path)
-            .unwrap();
-        file.write_all(http_notify_str.as_bytes()).unwrap();
-
     }
 
     tauri::Builder::default()
-        .manage(Arc::new(RwLock::new(AppState {
-            window: None,
-            serv_info: None,
-            shutdown_tx: Arc::new(Mutex::new(None)),
-        })))
+        .manage(Arc::new(RwLock::new(AppState::new())))
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(builder.invoke_handler())
         .setup(move |app| {
@@ -109,6 +637,16 @@ pub async fn run() {
                 tauri::async_runtime::spawn(async move {
                     let mut app_state = state.write().await;
                     app_state.window = Some(window.clone());
+                    drop(app_state);
+                    if let Err(e) = supervisor::run(state.clone()).await {
+                        tracing::error!("supervisor error: {}", e);
+                    }
+                    if let Err(e) = remote::run(state.clone()).await {
+                        tracing::error!("remote error: {}", e);
+                    }
+                    tauri::async_runtime::spawn(leak::run(state.clone()).instrument(tracing::info_span!("leak")));
+                    tauri::async_runtime::spawn(logging::run(state.clone()));
+                    monitor::run_sampler(state).instrument(tracing::info_span!("sampler")).await;
                 });
             }
             Ok(())
@@ -124,10 +662,9 @@ pub async fn run() {
                         let serv_info = app_state.serv_info.clone();
                         let mut shutdown_tx = shutdown_tx.lock().await;
                         if let Some(tx) = shutdown_tx.take() {
-                            println!("take sender shutdown_tx: {:?}", &serv_info);
                             match serv_info {
                                 Some(serv_info) => {
-                                    println!("send shutdown: {:?}", &serv_info);
+                                    tracing::info!(port = serv_info.port, "shutting down http server");
                                     let _ = tx.send(serv_info);
                                 }
                                 None => {}
@@ -135,7 +672,7 @@ pub async fn run() {
                         }
                     });
                 } else {
-                    println!("no state");
+                    tracing::warn!("no state on close");
                 }
             }
             _ => {},
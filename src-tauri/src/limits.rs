@@ -0,0 +1,236 @@
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::{ApiError, Result};
+
+pub const OOM_SCORE_ADJ_MIN: i32 = -1000;
+pub const OOM_SCORE_ADJ_MAX: i32 = 1000;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Resource {
+    Cpu,
+    Fsize,
+    Data,
+    Stack,
+    Core,
+    Rss,
+    Nproc,
+    Nofile,
+    Memlock,
+    As,
+    Locks,
+    Sigpending,
+    Msgqueue,
+    Nice,
+    Rtprio,
+    Rttime,
+}
+
+impl Resource {
+    /// Matches the row names of `/proc/<pid>/limits`.
+    fn from_proc_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Max cpu time" => Self::Cpu,
+            "Max file size" => Self::Fsize,
+            "Max data size" => Self::Data,
+            "Max stack size" => Self::Stack,
+            "Max core file size" => Self::Core,
+            "Max resident set" => Self::Rss,
+            "Max processes" => Self::Nproc,
+            "Max open files" => Self::Nofile,
+            "Max locked memory" => Self::Memlock,
+            "Max address space" => Self::As,
+            "Max file locks" => Self::Locks,
+            "Max pending signals" => Self::Sigpending,
+            "Max msgqueue size" => Self::Msgqueue,
+            "Max nice priority" => Self::Nice,
+            "Max realtime priority" => Self::Rtprio,
+            "Max realtime timeout" => Self::Rttime,
+            _ => return None,
+        })
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RlimitValue {
+    Unlimited,
+    Value(u64),
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Rlimit {
+    /// Row name as printed by the kernel, e.g. `Max open files`.
+    pub name: String,
+    pub resource: Option<Resource>,
+    pub soft: RlimitValue,
+    pub hard: RlimitValue,
+    pub units: Option<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessLimits {
+    pub pid: u32,
+    pub limits: Vec<Rlimit>,
+    pub oom_score: Option<i32>,
+    pub oom_score_adj: Option<i32>,
+}
+
+fn parse_value(value: &str) -> Option<RlimitValue> {
+    match value {
+        "unlimited" => Some(RlimitValue::Unlimited),
+        v => v.parse().ok().map(RlimitValue::Value),
+    }
+}
+
+/// Parses `/proc/<pid>/limits`, whose columns are aligned under the header.
+pub fn parse_limits(content: &str) -> Vec<Rlimit> {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else { return vec![] };
+    let (Some(soft_at), Some(hard_at), Some(units_at)) =
+        (header.find("Soft Limit"), header.find("Hard Limit"), header.find("Units")) else {
+        return vec![];
+    };
+    lines.filter_map(|line| {
+        let name = line.get(..soft_at)?.trim();
+        let soft = parse_value(line.get(soft_at..hard_at)?.trim())?;
+        let hard = parse_value(line.get(hard_at..units_at)?.trim())?;
+        let units = line.get(units_at..).map(|u| u.trim()).filter(|u| !u.is_empty()).map(|u| u.to_string());
+        Some(Rlimit { name: name.to_string(), resource: Resource::from_proc_name(name), soft, hard, units })
+    }).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc(pid: u32, file: &str) -> Result<String> {
+    let path = crate::procfs::proc_path(pid, file);
+    std::fs::read_to_string(&path).map_err(|e| ApiError::from_os_error(e, pid).with_path(&path))
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_limits(pid: u32) -> Result<ProcessLimits> {
+    Ok(ProcessLimits {
+        pid,
+        limits: parse_limits(&read_proc(pid, "limits")?),
+        oom_score: read_proc(pid, "oom_score").ok().and_then(|v| v.trim().parse().ok()),
+        oom_score_adj: read_proc(pid, "oom_score_adj").ok().and_then(|v| v.trim().parse().ok()),
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_limit(pid: u32, resource: Resource, soft: RlimitValue, hard: RlimitValue) -> Result<()> {
+    let raw = |value: &RlimitValue| match value {
+        RlimitValue::Unlimited => libc::RLIM_INFINITY,
+        RlimitValue::Value(v) => *v as libc::rlim_t,
+    };
+    let limit = libc::rlimit { rlim_cur: raw(&soft), rlim_max: raw(&hard) };
+    if limit.rlim_cur > limit.rlim_max {
+        return Err(ApiError::InvalidArgument("soft limit exceeds hard limit".into()));
+    }
+    let resource = match resource {
+        Resource::Cpu => libc::RLIMIT_CPU,
+        Resource::Fsize => libc::RLIMIT_FSIZE,
+        Resource::Data => libc::RLIMIT_DATA,
+        Resource::Stack => libc::RLIMIT_STACK,
+        Resource::Core => libc::RLIMIT_CORE,
+        Resource::Rss => libc::RLIMIT_RSS,
+        Resource::Nproc => libc::RLIMIT_NPROC,
+        Resource::Nofile => libc::RLIMIT_NOFILE,
+        Resource::Memlock => libc::RLIMIT_MEMLOCK,
+        Resource::As => libc::RLIMIT_AS,
+        Resource::Locks => libc::RLIMIT_LOCKS,
+        Resource::Sigpending => libc::RLIMIT_SIGPENDING,
+        Resource::Msgqueue => libc::RLIMIT_MSGQUEUE,
+        Resource::Nice => libc::RLIMIT_NICE,
+        Resource::Rtprio => libc::RLIMIT_RTPRIO,
+        Resource::Rttime => libc::RLIMIT_RTTIME,
+    };
+    if unsafe { libc::prlimit(pid as libc::pid_t, resource, &limit, std::ptr::null_mut()) } == -1 {
+        return Err(ApiError::last_os_error(pid).with_operation("prlimit"));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_oom_score_adj(pid: u32, value: i32) -> Result<()> {
+    if !(OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(&value) {
+        return Err(ApiError::InvalidArgument(format!(
+            "oom_score_adj {} is outside {}..={}", value, OOM_SCORE_ADJ_MIN, OOM_SCORE_ADJ_MAX
+        ).into()));
+    }
+    std::fs::write(crate::procfs::proc_path(pid, "oom_score_adj"), value.to_string())
+        .map_err(|e| ApiError::from_os_error(e, pid))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_limits(_pid: u32) -> Result<ProcessLimits> {
+    Err(ApiError::Unsupported("Resource limits are only supported on Linux".into()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_limit(_pid: u32, _resource: Resource, _soft: RlimitValue, _hard: RlimitValue) -> Result<()> {
+    Err(ApiError::Unsupported("Resource limits are only supported on Linux".into()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_oom_score_adj(_pid: u32, _value: i32) -> Result<()> {
+    Err(ApiError::Unsupported("oom_score_adj is only supported on Linux".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limits() {
+        let content = "\
+Limit                     Soft Limit           Hard Limit           Units     \n\
+Max cpu time              unlimited            unlimited            seconds   \n\
+Max open files            1024                 524288               files     \n\
+Max nice priority         0                    0                    \n";
+        let limits = parse_limits(content);
+        assert_eq!(limits.len(), 3);
+        assert_eq!(limits[0].resource, Some(Resource::Cpu));
+        assert_eq!(limits[0].soft, RlimitValue::Unlimited);
+        assert_eq!(limits[1].resource, Some(Resource::Nofile));
+        assert_eq!(limits[1].soft, RlimitValue::Value(1024));
+        assert_eq!(limits[1].hard, RlimitValue::Value(524288));
+        assert_eq!(limits[1].units.as_deref(), Some("files"));
+        assert_eq!(limits[2].units, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_limits() {
+        let limits = get_limits(std::process::id()).unwrap();
+        assert_eq!(limits.limits.len(), 16);
+        assert!(limits.limits.iter().all(|l| l.resource.is_some()));
+        assert!(limits.oom_score_adj.is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_set_limit_on_child() {
+        let mut child = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        let pid = child.id();
+        set_limit(pid, Resource::Nofile, RlimitValue::Value(256), RlimitValue::Value(512)).unwrap();
+        set_oom_score_adj(pid, 500).unwrap();
+        let limits = get_limits(pid).unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+        let nofile = limits.limits.iter().find(|l| l.resource == Some(Resource::Nofile)).unwrap();
+        assert_eq!(nofile.soft, RlimitValue::Value(256));
+        assert_eq!(nofile.hard, RlimitValue::Value(512));
+        assert_eq!(limits.oom_score_adj, Some(500));
+        assert!(matches!(set_oom_score_adj(pid, 5000), Err(ApiError::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use tokio::sync::{broadcast, RwLock};
+use tracing::field::{Field, Visit};
+use tracing::{span, Dispatch, Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+use crate::AppState;
+use crate::error::{ApiError, Result};
+use crate::utils::get_log_path;
+
+/// Log files are named `tr-process.<date>.log` and rotated daily.
+pub const LOG_PREFIX: &str = "tr-process";
+/// Daily files kept, today's included.
+pub const MAX_LOG_FILES: usize = 5;
+/// Entries kept in memory for `get_logs`.
+pub const BUFFER_CAPACITY: usize = 2000;
+pub const DEFAULT_LIMIT: u32 = 500;
+/// Initial level, e.g. `TR_PROCESS_LOG=debug`.
+pub const LEVEL_ENV: &str = "TR_PROCESS_LOG";
+/// Events logged while emitting; not forwarded to the window, which may be what failed.
+pub const EMIT_TARGET: &str = "emit";
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<tracing::Level> for LogLevel {
+    fn from(level: tracing::Level) -> Self {
+        match level {
+            tracing::Level::ERROR => LogLevel::Error,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(ApiError::InvalidArgument(format!("Unknown log level {}", s).into())),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+    /// Enclosing spans, outermost first, as `name{field=value}`.
+    pub spans: Vec<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LogQuery {
+    /// Least severe level to include.
+    pub level: Option<LogLevel>,
+    /// Only entries at or after this Unix time in milliseconds.
+    pub since: Option<u64>,
+    /// Newest entries to return; `DEFAULT_LIMIT` when unset.
+    pub limit: Option<u32>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+/// Our own crates at `level`; dependencies such as hyper are chatty below warn.
+fn env_filter(level: LogLevel) -> EnvFilter {
+    EnvFilter::new(format!("warn,tr_process={}", level.to_string().to_ascii_lowercase()))
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// Fields recorded on a span, kept in its registry extensions.
+struct SpanFields(BTreeMap<String, String>);
+
+fn span_label(name: &str, fields: Option<&SpanFields>) -> String {
+    let Some(SpanFields(fields)) = fields.filter(|fields| !fields.0.is_empty()) else {
+        return name.to_string();
+    };
+    let mut label = format!("{}{{", name);
+    for (i, (key, value)) in fields.iter().enumerate() {
+        let _ = write!(label, "{}{}={}", if i > 0 { " " } else { "" }, key, value);
+    }
+    label.push('}');
+    label
+}
+
+struct Buffer {
+    next_seq: AtomicU64,
+    entries: Mutex<VecDeque<LogEntry>>,
+    live: broadcast::Sender<LogEntry>,
+}
+
+/// Keeps recent events for `get_logs` and broadcasts them for the live log view;
+/// formatting to stderr and the log file is left to `tracing_subscriber::fmt`.
+struct BufferLayer(Arc<Buffer>);
+
+impl<S> Layer<S> for BufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(visitor.fields));
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+                fields.0.extend(visitor.fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let spans = ctx.event_scope(event)
+            .map(|scope| scope.from_root()
+                .map(|span| span_label(span.name(), span.extensions().get::<SpanFields>()))
+                .collect())
+            .unwrap_or_default();
+        let entry = LogEntry {
+            seq: self.0.next_seq.fetch_add(1, Ordering::Relaxed),
+            timestamp: now_ms(),
+            level: LogLevel::from(*event.metadata().level()),
+            target: event.metadata().target().to_string(),
+            message: visitor.message.unwrap_or_default(),
+            fields: visitor.fields,
+            spans,
+        };
+        {
+            let mut entries = self.0.entries.lock().unwrap();
+            if entries.len() >= BUFFER_CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+        let _ = self.0.live.send(entry);
+    }
+}
+
+/// Handle on the installed subscriber: stderr and a daily log file through
+/// `tracing_subscriber::fmt`, an in-memory buffer, and a level that can be changed while running.
+pub struct Logger {
+    level: AtomicU8,
+    filter: reload::Handle<EnvFilter, Registry>,
+    buffer: Arc<Buffer>,
+    /// Flushes the file writer's queue when dropped.
+    _file_guard: Option<WorkerGuard>,
+}
+
+impl Logger {
+    /// Builds the logger and the subscriber it controls; `dir` is where log files go.
+    pub fn new(level: LogLevel, dir: Option<&Path>) -> (Self, Dispatch) {
+        let (filter, handle) = reload::Layer::new(env_filter(level));
+        let appender = dir.and_then(|dir| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_PREFIX)
+                .filename_suffix("log")
+                .max_log_files(MAX_LOG_FILES)
+                .build(dir)
+                .map_err(|e| eprintln!("log files in {} unavailable: {}", dir.display(), e))
+                .ok()
+        });
+        let (file_layer, file_guard) = match appender {
+            Some(appender) => {
+                let (writer, guard) = tracing_appender::non_blocking(appender);
+                (Some(tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false)), Some(guard))
+            }
+            None => (None, None),
+        };
+        let (live, _) = broadcast::channel(256);
+        let buffer = Arc::new(Buffer {
+            next_seq: AtomicU64::new(1),
+            entries: Mutex::new(VecDeque::new()),
+            live,
+        });
+        let subscriber = Registry::default()
+            .with(filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .with(file_layer)
+            .with(BufferLayer(buffer.clone()));
+        let logger = Self {
+            level: AtomicU8::new(level as u8),
+            filter: handle,
+            buffer,
+            _file_guard: file_guard,
+        };
+        (logger, Dispatch::new(subscriber))
+    }
+
+    pub fn level(&self) -> LogLevel {
+        match self.level.load(Ordering::Relaxed) {
+            0 => LogLevel::Error,
+            1 => LogLevel::Warn,
+            2 => LogLevel::Info,
+            3 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+        // only fails once the subscriber is gone, when nothing is logged anyway
+        let _ = self.filter.reload(env_filter(level));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.buffer.live.subscribe()
+    }
+
+    /// Buffered entries matching the query, oldest first.
+    pub fn query(&self, query: &LogQuery) -> Vec<LogEntry> {
+        let buffer = self.buffer.entries.lock().unwrap();
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT) as usize;
+        let mut entries: Vec<LogEntry> = buffer.iter().rev()
+            .filter(|e| query.level.is_none_or(|level| e.level <= level))
+            .filter(|e| query.since.is_none_or(|since| e.timestamp >= since))
+            .take(limit)
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+}
+
+static LOGGER: OnceLock<Arc<Logger>> = OnceLock::new();
+
+/// Installs the global logger; later calls return the one already installed.
+pub fn init() -> Arc<Logger> {
+    LOGGER.get_or_init(|| {
+        let level = std::env::var(LEVEL_ENV).ok()
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(LogLevel::Info);
+        let dir = get_log_path().ok();
+        let (logger, dispatch) = Logger::new(level, dir.as_deref());
+        if let Err(e) = tracing::dispatcher::set_global_default(dispatch) {
+            eprintln!("logger already installed: {}", e);
+        }
+        Arc::new(logger)
+    }).clone()
+}
+
+pub fn logger() -> Result<Arc<Logger>> {
+    LOGGER.get().cloned().ok_or(ApiError::Unavailable("Logging is not initialized".into()))
+}
+
+/// Forwards every log entry to the window and WebSocket clients as a `log-entry` event.
+pub async fn run(app_state: Arc<RwLock<AppState>>) {
+    let Ok(logger) = logger() else { return };
+    let mut entries = logger.subscribe();
+    loop {
+        match entries.recv().await {
+            Ok(entry) if entry.target == EMIT_TARGET => {}
+            Ok(entry) => app_state.read().await.emit("log-entry", entry),
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logger() {
+        let (logger, dispatch) = Logger::new(LogLevel::Info, None);
+        tracing::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("request", path = "/api");
+            let _enter = span.enter();
+            tracing::info!(target: "tr_process_lib::test", pid = 42, "started");
+            tracing::debug!(target: "tr_process_lib::test", "hidden");
+            tracing::warn!(target: "tr_process_lib::test", "slow");
+            tracing::info!(target: "hyper::proto", "chatty");
+        });
+        let entries = logger.query(&LogQuery::default());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].message, "started");
+        assert_eq!(entries[0].fields.get("pid").map(|v| v.as_str()), Some("42"));
+        assert_eq!(entries[0].spans, vec!["request{path=/api}".to_string()]);
+
+        logger.set_level(LogLevel::Debug);
+        tracing::dispatcher::with_default(&dispatch, || {
+            tracing::debug!(target: "tr_process_lib::test", "shown");
+        });
+        let warnings = logger.query(&LogQuery { level: Some(LogLevel::Warn), ..LogQuery::default() });
+        assert_eq!(warnings.len(), 1);
+        let newest = logger.query(&LogQuery { limit: Some(1), ..LogQuery::default() });
+        assert_eq!(newest[0].message, "shown");
+    }
+
+    #[test]
+    fn test_log_file() {
+        let dir = std::env::temp_dir().join(format!("tr-process-logs-{}", std::process::id()));
+        let (logger, dispatch) = Logger::new(LogLevel::Info, Some(&dir));
+        tracing::dispatcher::with_default(&dispatch, || {
+            tracing::info!(target: "tr_process_lib::test", "written");
+        });
+        // dropping the guard flushes the writer thread
+        drop((logger, dispatch));
+        let contents: String = std::fs::read_dir(&dir).unwrap()
+            .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+            .collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(contents.contains("written"));
+    }
+}
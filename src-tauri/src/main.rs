@@ -1,7 +1,17 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use clap::Parser;
+use tr_process_lib::daemon::{self, DaemonArgs};
+
 #[tokio::main]
 async fn main() {
+    if daemon::requested() {
+        if let Err(e) = daemon::run(DaemonArgs::parse()).await {
+            tracing::error!("daemon error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     tr_process_lib::run().await
 }
@@ -0,0 +1,160 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::Result;
+use crate::monitor::SystemTotals;
+use crate::sys::ProcessInfo;
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "metrics.json";
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MetricsConfig {
+    /// Process names exported per process; `*` matches any run of characters.
+    pub allow: Vec<String>,
+}
+
+impl MetricsConfig {
+    pub fn allows(&self, name: &str) -> bool {
+        self.allow.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<MetricsConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(MetricsConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &MetricsConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+/// Name, type, help text and value of a per-process metric.
+type ProcessMetric = (&'static str, &'static str, &'static str, fn(&ProcessInfo) -> Option<f64>);
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Renders the snapshot in the Prometheus text exposition format.
+pub fn render(config: &MetricsConfig, processes: &[ProcessInfo], totals: &SystemTotals) -> String {
+    let mut out = String::new();
+
+    header(&mut out, "tr_process_count", "gauge", "Number of processes in the last snapshot.");
+    let _ = writeln!(out, "tr_process_count {}", processes.iter().filter(|p| !p.is_missing()).count());
+
+    header(&mut out, "tr_system_cpu_usage_percent", "gauge", "Global CPU usage.");
+    let _ = writeln!(out, "tr_system_cpu_usage_percent {}", totals.cpu_usage);
+    header(&mut out, "tr_system_memory_total_bytes", "gauge", "Total memory.");
+    let _ = writeln!(out, "tr_system_memory_total_bytes {}", totals.total_memory);
+    header(&mut out, "tr_system_memory_used_bytes", "gauge", "Used memory.");
+    let _ = writeln!(out, "tr_system_memory_used_bytes {}", totals.used_memory);
+    header(&mut out, "tr_system_swap_total_bytes", "gauge", "Total swap.");
+    let _ = writeln!(out, "tr_system_swap_total_bytes {}", totals.total_swap);
+    header(&mut out, "tr_system_swap_used_bytes", "gauge", "Used swap.");
+    let _ = writeln!(out, "tr_system_swap_used_bytes {}", totals.used_swap);
+
+    let mut allowed: Vec<&ProcessInfo> = processes.iter()
+        .filter(|p| p.name.as_deref().is_some_and(|name| config.allows(name)))
+        .collect();
+    allowed.sort_by_key(|p| p.pid);
+    let labels: Vec<String> = allowed.iter()
+        .map(|p| format!("pid=\"{}\",name=\"{}\"", p.pid, escape_label(p.name.as_deref().unwrap_or_default())))
+        .collect();
+
+    let per_process: [ProcessMetric; 6] = [
+        ("tr_process_cpu_usage_percent", "gauge", "CPU usage of the process.",
+            |p| p.cpu_usage.map(|v| v as f64)),
+        ("tr_process_memory_bytes", "gauge", "Resident memory of the process.",
+            |p| p.memory.map(|v| v as f64)),
+        ("tr_process_disk_read_bytes_total", "counter", "Bytes read from disk by the process.",
+            |p| p.disk_usage.as_ref().map(|d| d.total_read_bytes as f64)),
+        ("tr_process_disk_written_bytes_total", "counter", "Bytes written to disk by the process.",
+            |p| p.disk_usage.as_ref().map(|d| d.total_write_bytes as f64)),
+        ("tr_process_sockets", "gauge", "Open TCP and UDP sockets of the process.",
+            |p| Some(p.socks.len() as f64)),
+        ("tr_process_uptime_seconds", "gauge", "Seconds since the process started.",
+            |p| p.uptime.map(|v| v as f64)),
+    ];
+    for (name, kind, help, value) in per_process {
+        header(&mut out, name, kind, help);
+        for (p, labels) in allowed.iter().zip(labels.iter()) {
+            if let Some(v) = value(p) {
+                let _ = writeln!(out, "{}{{{}}} {}", name, labels, v);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("postgres", "postgres"));
+        assert!(!glob_match("postgres", "postgres.exe"));
+        assert!(glob_match("node*", "node.exe"));
+        assert!(glob_match("*sql*", "mysqld"));
+        assert!(!glob_match("a*b*c", "acb"));
+    }
+
+    #[test]
+    fn test_render() {
+        let config = MetricsConfig { allow: vec!["node*".to_string()] };
+        let processes = vec![
+            ProcessInfo { pid: 1, name: Some("node".to_string()), memory: Some(1024), ..ProcessInfo::default() },
+            ProcessInfo { pid: 2, name: Some("bash".to_string()), memory: Some(2048), ..ProcessInfo::default() },
+        ];
+        let out = render(&config, &processes, &SystemTotals::default());
+        assert!(out.contains("tr_process_count 2\n"));
+        assert!(out.contains("tr_process_memory_bytes{pid=\"1\",name=\"node\"} 1024\n"));
+        assert!(!out.contains("name=\"bash\""));
+    }
+}
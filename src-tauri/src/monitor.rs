@@ -0,0 +1,382 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use sysinfo::{Disks, Networks, System};
+use tokio::sync::{watch, RwLock};
+
+use crate::AppState;
+use crate::diskio::{self, DeviceIo, DiskStat};
+use crate::anomaly::{self, ProcessAnomalies, Reparented};
+use crate::error::Result;
+use crate::integrity::{IntegrityChecker, IntegrityConfig};
+use crate::overview::{self, rate, SystemOverview};
+use crate::smaps::{self, MemoryDetail, SMAPS_INTERVAL};
+use crate::sys::{self, ProcessInfo, SockInfo};
+
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+pub const EXIT_LOG_CAPACITY: usize = 1000;
+
+/// Exits reported by sources that actually observe a process ending
+/// (children we spawned, the Linux proc connector).
+#[derive(Default)]
+pub struct ExitReports {
+    /// Exit codes keyed by pid, picked up when the pid drops out of a snapshot.
+    pub codes: HashMap<u32, i32>,
+    /// Processes that started and exited between two snapshots.
+    pub unseen: Vec<ProcessExit>,
+}
+
+pub type SharedExitReports = Arc<std::sync::Mutex<ExitReports>>;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessExit {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: Option<String>,
+    pub exe: Option<String>,
+    pub cpu_usage: Option<f32>,
+    pub memory: Option<u64>,
+    pub accumulated_cpu_time: Option<u64>,
+    /// Seconds the process had been running when it was last seen.
+    pub runtime: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub exited_at: u64,
+}
+
+impl ProcessExit {
+    fn new(info: &ProcessInfo, exit_code: Option<i32>, exited_at: u64) -> Self {
+        Self {
+            pid: info.pid,
+            ppid: info.ppid,
+            name: info.name.clone(),
+            exe: info.exe.clone(),
+            cpu_usage: info.cpu_usage,
+            memory: info.memory,
+            accumulated_cpu_time: info.accumulated_cpu_time,
+            runtime: info.uptime,
+            exit_code,
+            exited_at,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SystemTotals {
+    pub cpu_usage: f32,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+}
+
+pub struct Monitor {
+    system: System,
+    disks: Disks,
+    networks: Networks,
+    diskstats: Vec<DiskStat>,
+    disk_io: Vec<DeviceIo>,
+    /// Time between the last two refreshes, for per-second rates.
+    elapsed: Option<Duration>,
+    refreshed_at: Option<Instant>,
+    processes: HashMap<u32, ProcessInfo>,
+    sockets: Vec<SockInfo>,
+    exit_log: VecDeque<ProcessExit>,
+    exit_reports: SharedExitReports,
+    spawned: HashSet<u32>,
+    /// Last `smaps_rollup` reads with the start time of the process they belong to.
+    memory_details: HashMap<u32, (Option<u64>, MemoryDetail)>,
+    smaps_at: Option<Instant>,
+    integrity: IntegrityChecker,
+    reparented: Reparented,
+    anomalies: ProcessAnomalies,
+    /// Set after every completed refresh, so waiters re-check the new snapshot.
+    refreshed: watch::Sender<bool>,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self {
+            system: System::new_all(),
+            disks: Disks::new_with_refreshed_list(),
+            networks: Networks::new_with_refreshed_list(),
+            diskstats: Vec::new(),
+            disk_io: Vec::new(),
+            elapsed: None,
+            refreshed_at: None,
+            processes: HashMap::new(),
+            sockets: Vec::new(),
+            exit_log: VecDeque::new(),
+            exit_reports: Arc::new(std::sync::Mutex::new(ExitReports::default())),
+            spawned: HashSet::new(),
+            memory_details: HashMap::new(),
+            smaps_at: None,
+            integrity: IntegrityChecker::default(),
+            reparented: Reparented::new(),
+            anomalies: ProcessAnomalies::default(),
+            refreshed: watch::channel(false).0,
+        }
+    }
+
+    pub fn exit_reports(&self) -> SharedExitReports {
+        self.exit_reports.clone()
+    }
+
+    pub fn mark_spawned(&mut self, pid: u32) {
+        self.spawned.insert(pid);
+    }
+
+    /// Whether `refresh` has run yet; before that the snapshot is empty.
+    pub fn is_sampled(&self) -> bool {
+        *self.refreshed.borrow()
+    }
+
+    /// Notified after every refresh.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.refreshed.subscribe()
+    }
+
+    pub fn processes(&self) -> Vec<ProcessInfo> {
+        self.processes.values().cloned().collect()
+    }
+
+    pub fn sockets(&self) -> Vec<SockInfo> {
+        self.sockets.clone()
+    }
+
+    pub fn totals(&self) -> SystemTotals {
+        SystemTotals {
+            cpu_usage: self.system.global_cpu_usage(),
+            total_memory: self.system.total_memory(),
+            used_memory: self.system.used_memory(),
+            total_swap: self.system.total_swap(),
+            used_swap: self.system.used_swap(),
+        }
+    }
+
+    pub fn overview(&self) -> SystemOverview {
+        overview::collect(&self.system, &self.disks, &self.networks, self.elapsed)
+    }
+
+    pub fn disk_io(&self) -> Vec<DeviceIo> {
+        self.disk_io.clone()
+    }
+
+    pub fn integrity_config(&self) -> IntegrityConfig {
+        self.integrity.config()
+    }
+
+    pub fn set_integrity_config(&mut self, config: IntegrityConfig) {
+        self.integrity.set_config(config);
+    }
+
+    pub fn anomalies(&self) -> ProcessAnomalies {
+        self.anomalies.clone()
+    }
+
+    pub fn exit_log(&self) -> Vec<ProcessExit> {
+        self.exit_log.iter().cloned().collect()
+    }
+
+    /// Takes a new snapshot and returns the processes that disappeared since the previous one.
+    pub fn refresh(&mut self) -> Result<Vec<ProcessExit>> {
+        self.system.refresh_all();
+        self.disks.refresh(true);
+        self.networks.refresh(true);
+        let now = Instant::now();
+        self.elapsed = self.refreshed_at.map(|at| now.duration_since(at));
+        self.refreshed_at = Some(now);
+        let diskstats = diskio::read_diskstats();
+        self.disk_io = diskio::device_io(&self.diskstats, &diskstats, self.elapsed);
+        self.diskstats = diskstats;
+        let sockets = sys::get_sockets()?;
+        let mut processes = sys::collect_processes_with(&self.system, &sockets)?;
+        let exited_at = now_secs();
+
+        self.spawned.retain(|pid| processes.contains_key(pid));
+        for pid in self.spawned.iter() {
+            if let Some(info) = processes.get_mut(pid) {
+                info.spawned = Some(true);
+            }
+        }
+
+        for info in processes.values_mut() {
+            let Some(prev) = self.processes.get(&info.pid).filter(|prev| prev.start_time == info.start_time) else { continue };
+            let (Some(disk), Some(prev_disk)) = (info.disk_usage.as_mut(), prev.disk_usage.as_ref()) else { continue };
+            let per_sec = |cur: Option<u64>, prev: Option<u64>| rate(cur?.checked_sub(prev?)?, self.elapsed);
+            disk.read_syscalls_per_sec = per_sec(disk.read_syscalls, prev_disk.read_syscalls);
+            disk.write_syscalls_per_sec = per_sec(disk.write_syscalls, prev_disk.write_syscalls);
+        }
+
+        if self.smaps_at.is_none_or(|at| at.elapsed() >= SMAPS_INTERVAL) {
+            self.memory_details = processes.values()
+                .filter_map(|p| smaps::read(p.pid).map(|m| (p.pid, (p.start_time, m))))
+                .collect();
+            self.smaps_at = Some(Instant::now());
+        }
+        for info in processes.values_mut() {
+            if let Some((start_time, detail)) = self.memory_details.get(&info.pid) {
+                if *start_time == info.start_time {
+                    info.memory_detail = Some(detail.clone());
+                }
+            }
+        }
+
+        self.integrity.apply(&mut processes);
+
+        let exits = diff_exits(&self.processes, &processes, &mut self.exit_reports.lock().unwrap(), exited_at);
+        log_exits(&mut self.exit_log, &exits);
+        anomaly::track_reparented(&mut self.reparented, &self.processes, &processes);
+        self.anomalies = anomaly::report(&processes, &self.processes, &self.reparented);
+        self.processes = processes;
+        self.sockets = sockets;
+        self.refreshed.send_replace(true);
+        Ok(exits)
+    }
+}
+
+/// Processes in `prev` that are gone from `cur`, or whose pid now belongs to a newer process,
+/// followed by the reported exits neither snapshot saw.
+fn diff_exits(prev: &HashMap<u32, ProcessInfo>, cur: &HashMap<u32, ProcessInfo>, reports: &mut ExitReports, exited_at: u64) -> Vec<ProcessExit> {
+    let mut exits: Vec<ProcessExit> = prev.values()
+        .filter(|prev| !prev.is_missing())
+        .filter(|prev| match cur.get(&prev.pid) {
+            Some(cur) => cur.start_time != prev.start_time,
+            None => true,
+        })
+        .map(|prev| ProcessExit::new(prev, reports.codes.remove(&prev.pid), exited_at))
+        .collect();
+    let unseen: Vec<ProcessExit> = reports.unseen.drain(..)
+        .filter(|exit| !prev.contains_key(&exit.pid))
+        .collect();
+    exits.extend(unseen);
+    // codes for pids we never saw alive are of no further use
+    reports.codes.retain(|pid, _| cur.contains_key(pid));
+    exits
+}
+
+fn log_exits(exit_log: &mut VecDeque<ProcessExit>, exits: &[ProcessExit]) {
+    for exit in exits {
+        if exit_log.len() >= EXIT_LOG_CAPACITY {
+            exit_log.pop_front();
+        }
+        exit_log.push_back(exit.clone());
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Refreshes the shared monitor and emits a `process-exited` event for every process that went away.
+pub async fn refresh(app_state: &Arc<RwLock<AppState>>) -> Result<Vec<ProcessInfo>> {
+    let monitor = app_state.read().await.monitor.clone();
+    let (exits, processes) = tokio::task::spawn_blocking(move || {
+        let mut monitor = monitor.blocking_lock();
+        monitor.refresh().map(|exits| (exits, monitor.processes()))
+    }).await??;
+
+    let state = app_state.read().await;
+    for exit in exits {
+        state.emit("process-exited", exit);
+    }
+    Ok(processes)
+}
+
+pub async fn run_sampler(app_state: Arc<RwLock<AppState>>) {
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = refresh(&app_state).await {
+            tracing::error!("sampler error: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, start_time: u64) -> ProcessInfo {
+        ProcessInfo { pid, start_time: Some(start_time), ..ProcessInfo::default() }
+    }
+
+    fn snapshot(processes: Vec<ProcessInfo>) -> HashMap<u32, ProcessInfo> {
+        processes.into_iter().map(|p| (p.pid, p)).collect()
+    }
+
+    fn exit(pid: u32) -> ProcessExit {
+        ProcessExit::new(&process(pid, 0), None, 0)
+    }
+
+    #[test]
+    fn test_diff_exits() {
+        let prev = snapshot(vec![process(1, 1), process(2, 2), process(3, 3)]);
+        // 2 exited, 3 is a new process on a reused pid
+        let cur = snapshot(vec![process(1, 1), process(3, 30), process(4, 4)]);
+        let mut reports = ExitReports::default();
+        let exits = diff_exits(&prev, &cur, &mut reports, 100);
+        let mut pids: Vec<u32> = exits.iter().map(|e| e.pid).collect();
+        pids.sort();
+        assert_eq!(pids, vec![2, 3]);
+        assert!(exits.iter().all(|e| e.exited_at == 100 && e.exit_code.is_none()));
+    }
+
+    #[test]
+    fn test_diff_exits_reported_code() {
+        let prev = snapshot(vec![process(1, 1), process(2, 2)]);
+        let cur = snapshot(vec![process(1, 1)]);
+        let mut reports = ExitReports::default();
+        reports.codes.insert(2, 7);
+        // 9 was never seen alive, 1 is still running
+        reports.codes.insert(9, 1);
+        reports.codes.insert(1, 0);
+        let exits = diff_exits(&prev, &cur, &mut reports, 0);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].exit_code, Some(7));
+        assert_eq!(reports.codes, HashMap::from([(1, 0)]));
+    }
+
+    #[test]
+    fn test_diff_exits_unseen() {
+        let prev = snapshot(vec![process(1, 1), process(2, 2)]);
+        let cur = snapshot(vec![process(1, 1)]);
+        // 2 is already reported by the snapshot diff, 5 started and ended in between
+        let mut reports = ExitReports { unseen: vec![exit(2), exit(5)], ..ExitReports::default() };
+        let exits = diff_exits(&prev, &cur, &mut reports, 0);
+        let mut pids: Vec<u32> = exits.iter().map(|e| e.pid).collect();
+        pids.sort();
+        assert_eq!(pids, vec![2, 5]);
+        assert!(reports.unseen.is_empty());
+    }
+
+    #[test]
+    fn test_diff_exits_missing() {
+        let prev = snapshot(vec![ProcessInfo { missing: Some(true), ..process(1, 1) }]);
+        let exits = diff_exits(&prev, &HashMap::new(), &mut ExitReports::default(), 0);
+        assert!(exits.is_empty());
+    }
+
+    #[test]
+    fn test_log_exits() {
+        let mut exit_log = VecDeque::new();
+        let exits: Vec<ProcessExit> = (0..EXIT_LOG_CAPACITY as u32 + 5).map(exit).collect();
+        log_exits(&mut exit_log, &exits);
+        assert_eq!(exit_log.len(), EXIT_LOG_CAPACITY);
+        assert_eq!(exit_log.front().unwrap().pid, 5);
+        assert_eq!(exit_log.back().unwrap().pid, EXIT_LOG_CAPACITY as u32 + 4);
+    }
+}
@@ -0,0 +1,269 @@
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::sys::{ProcessInfo, SockInfo, SockProtocol, SockState};
+
+/// Namespace inodes of a process, as in `/proc/<pid>/ns/*`.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Namespaces {
+    pub pid: Option<u64>,
+    pub net: Option<u64>,
+    pub mnt: Option<u64>,
+    pub uts: Option<u64>,
+    pub ipc: Option<u64>,
+    pub user: Option<u64>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum NamespaceKind {
+    Pid,
+    Net,
+    Mnt,
+    Uts,
+    Ipc,
+    User,
+}
+
+impl Namespaces {
+    pub fn get(&self, kind: &NamespaceKind) -> Option<u64> {
+        match kind {
+            NamespaceKind::Pid => self.pid,
+            NamespaceKind::Net => self.net,
+            NamespaceKind::Mnt => self.mnt,
+            NamespaceKind::Uts => self.uts,
+            NamespaceKind::Ipc => self.ipc,
+            NamespaceKind::User => self.user,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct NamespaceGroup {
+    pub kind: NamespaceKind,
+    pub inode: u64,
+    pub pids: Vec<u32>,
+    /// Whether tr-process itself lives in this namespace.
+    pub own: bool,
+}
+
+/// Parses a `/proc/<pid>/ns/*` link target such as `net:[4026531840]`.
+pub fn parse_ns_link(link: &str) -> Option<u64> {
+    link.split_once(":[")?.1.strip_suffix(']')?.parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn read_ns(pid: &str, name: &str) -> Option<u64> {
+    let link = std::fs::read_link(format!("/proc/{}/ns/{}", pid, name)).ok()?;
+    parse_ns_link(&link.to_string_lossy())
+}
+
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<Namespaces> {
+    let pid = pid.to_string();
+    let namespaces = Namespaces {
+        pid: read_ns(&pid, "pid"),
+        net: read_ns(&pid, "net"),
+        mnt: read_ns(&pid, "mnt"),
+        uts: read_ns(&pid, "uts"),
+        ipc: read_ns(&pid, "ipc"),
+        user: read_ns(&pid, "user"),
+    };
+    (namespaces != Namespaces::default()).then_some(namespaces)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<Namespaces> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn own(kind: &NamespaceKind) -> Option<u64> {
+    read(std::process::id()).and_then(|ns| ns.get(kind))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn own(_kind: &NamespaceKind) -> Option<u64> {
+    None
+}
+
+/// Groups processes by their namespace of the given kind.
+pub fn groups(processes: &[ProcessInfo], kind: NamespaceKind) -> Vec<NamespaceGroup> {
+    let own = own(&kind);
+    let mut by_inode: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+    for p in processes.iter() {
+        if let Some(inode) = p.namespaces.as_ref().and_then(|ns| ns.get(&kind)) {
+            by_inode.entry(inode).or_default().push(p.pid);
+        }
+    }
+    by_inode.into_iter().map(|(inode, mut pids)| {
+        pids.sort();
+        NamespaceGroup { kind: kind.clone(), inode, pids, own: own == Some(inode) }
+    }).collect()
+}
+
+fn parse_ipv4(hex: &str) -> Option<Ipv4Addr> {
+    // the kernel prints the in-memory address as a native-endian u32
+    Some(Ipv4Addr::from(u32::from_str_radix(hex, 16).ok()?.to_ne_bytes()))
+}
+
+fn parse_ipv6(hex: &str) -> Option<Ipv6Addr> {
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut octets = [0u8; 16];
+    for i in 0..4 {
+        let word = u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).ok()?;
+        octets[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+    }
+    Some(Ipv6Addr::from(octets))
+}
+
+fn parse_endpoint(field: &str) -> Option<(String, u16)> {
+    let (addr, port) = field.split_once(':')?;
+    let addr = match addr.len() {
+        8 => parse_ipv4(addr)?.to_string(),
+        _ => parse_ipv6(addr)?.to_string(),
+    };
+    Some((addr, u16::from_str_radix(port, 16).ok()?))
+}
+
+fn tcp_state(code: &str) -> SockState {
+    match code {
+        "01" => SockState::Established,
+        "02" => SockState::SynSent,
+        "03" => SockState::SynReceived,
+        "04" => SockState::FinWait1,
+        "05" => SockState::FinWait2,
+        "06" => SockState::TimeWait,
+        "07" => SockState::Closed,
+        "08" => SockState::CloseWait,
+        "09" => SockState::LastAck,
+        "0A" => SockState::Listen,
+        "0B" => SockState::Closing,
+        _ => SockState::Unknown,
+    }
+}
+
+/// Parses `/proc/<pid>/net/{tcp,tcp6,udp,udp6}`; returns each socket with its inode.
+pub fn parse_net_sockets(content: &str, protocol: SockProtocol) -> Vec<(u64, SockInfo)> {
+    content.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (local_addr, local_port) = parse_endpoint(fields.get(1)?)?;
+        let inode: u64 = fields.get(9)?.parse().ok()?;
+        let (remote_addr, remote_port, state) = match protocol {
+            SockProtocol::Tcp => {
+                let (addr, port) = parse_endpoint(fields.get(2)?)?;
+                (Some(addr), Some(port), Some(tcp_state(fields.get(3)?)))
+            }
+            SockProtocol::Udp => (None, None, None),
+        };
+        Some((inode, SockInfo {
+            local_addr,
+            local_port,
+            protocol: protocol.clone(),
+            pids: vec![],
+            remote_addr,
+            remote_port,
+            state,
+            netns: None,
+        }))
+    }).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn socket_inodes(pid: u32) -> Vec<u64> {
+    let Ok(entries) = std::fs::read_dir(crate::procfs::proc_path(pid, "fd")) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| std::fs::read_link(e.path()).ok())
+        .filter_map(|link| {
+            let link = link.to_string_lossy().to_string();
+            link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect()
+}
+
+/// Sockets of every network namespace other than our own, which netstat2 can't see.
+#[cfg(target_os = "linux")]
+pub fn foreign_sockets() -> Vec<SockInfo> {
+    let own = own(&NamespaceKind::Net);
+    let mut members: HashMap<u64, Vec<u32>> = HashMap::new();
+    for pid in crate::procfs::list_pids() {
+        if let Some(net) = read_ns(&pid.to_string(), "net") {
+            if Some(net) != own {
+                members.entry(net).or_default().push(pid);
+            }
+        }
+    }
+
+    let mut sockets = vec![];
+    for (netns, pids) in members {
+        let mut owners: HashMap<u64, Vec<u32>> = HashMap::new();
+        for pid in pids.iter() {
+            for inode in socket_inodes(*pid) {
+                owners.entry(inode).or_default().push(*pid);
+            }
+        }
+        // any member sees the namespace's tables
+        let Some(reader) = pids.first() else { continue };
+        for (file, protocol) in [("tcp", SockProtocol::Tcp), ("tcp6", SockProtocol::Tcp), ("udp", SockProtocol::Udp), ("udp6", SockProtocol::Udp)] {
+            let Ok(content) = std::fs::read_to_string(crate::procfs::proc_path(*reader, &format!("net/{}", file))) else { continue };
+            for (inode, mut sock) in parse_net_sockets(&content, protocol) {
+                sock.pids = owners.get(&inode).cloned().unwrap_or_default();
+                sock.netns = Some(netns);
+                sockets.push(sock);
+            }
+        }
+    }
+    sockets
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn foreign_sockets() -> Vec<SockInfo> {
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(parse_ns_link("net:[4026531840]"), Some(4026531840));
+        assert_eq!(parse_ns_link("net"), None);
+    }
+
+    #[test]
+    fn test_parse_net_sockets() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0\n";
+        let sockets = parse_net_sockets(tcp, SockProtocol::Tcp);
+        assert_eq!(sockets.len(), 1);
+        let (inode, sock) = &sockets[0];
+        assert_eq!(*inode, 31337);
+        assert_eq!(sock.local_addr, "127.0.0.1");
+        assert_eq!(sock.local_port, 8080);
+        assert!(matches!(sock.state, Some(SockState::Listen)));
+
+        let udp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n  1: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 4242 2 0000000000000000 0\n";
+        let sockets = parse_net_sockets(udp6, SockProtocol::Udp);
+        assert_eq!(sockets[0].1.local_addr, "::1");
+        assert_eq!(sockets[0].1.local_port, 53);
+        assert!(sockets[0].1.state.is_none());
+    }
+}
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use sysinfo::{Disks, Networks, System};
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct CpuCore {
+    pub name: String,
+    pub cpu_usage: f32,
+    /// MHz
+    pub frequency: u64,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct DiskUsage {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub kind: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub used_space: u64,
+    pub is_removable: bool,
+    pub is_read_only: bool,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub mac_address: String,
+    pub ip_addresses: Vec<String>,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    /// Rates over the last sampler tick; unset until two samples were taken.
+    pub received_per_sec: Option<f64>,
+    pub transmitted_per_sec: Option<f64>,
+    pub packets_received_per_sec: Option<f64>,
+    pub packets_transmitted_per_sec: Option<f64>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct SystemOverview {
+    pub hostname: Option<String>,
+    pub kernel_version: Option<String>,
+    pub os_version: Option<String>,
+    /// Seconds since boot.
+    pub uptime: u64,
+    /// Unix time of boot, in seconds.
+    pub boot_time: u64,
+    pub cpu_usage: f32,
+    pub cpus: Vec<CpuCore>,
+    pub load_average: LoadAverage,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub available_memory: u64,
+    pub free_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    pub disks: Vec<DiskUsage>,
+    pub networks: Vec<NetworkInterface>,
+}
+
+pub fn rate(delta: u64, elapsed: Option<Duration>) -> Option<f64> {
+    let secs = elapsed?.as_secs_f64();
+    (secs > 0.0).then(|| delta as f64 / secs)
+}
+
+/// `elapsed` is the time between the last two refreshes of `networks`.
+pub fn collect(system: &System, disks: &Disks, networks: &Networks, elapsed: Option<Duration>) -> SystemOverview {
+    let load = System::load_average();
+    let mut disks: Vec<DiskUsage> = disks.list().iter().map(|disk| DiskUsage {
+        name: disk.name().to_string_lossy().to_string(),
+        mount_point: disk.mount_point().to_string_lossy().to_string(),
+        file_system: disk.file_system().to_string_lossy().to_string(),
+        kind: disk.kind().to_string(),
+        total_space: disk.total_space(),
+        available_space: disk.available_space(),
+        used_space: disk.total_space().saturating_sub(disk.available_space()),
+        is_removable: disk.is_removable(),
+        is_read_only: disk.is_read_only(),
+    }).collect();
+    disks.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    let mut networks: Vec<NetworkInterface> = networks.iter().map(|(name, data)| NetworkInterface {
+        name: name.clone(),
+        mac_address: data.mac_address().to_string(),
+        ip_addresses: data.ip_networks().iter().map(|ip| format!("{}/{}", ip.addr, ip.prefix)).collect(),
+        total_received: data.total_received(),
+        total_transmitted: data.total_transmitted(),
+        total_packets_received: data.total_packets_received(),
+        total_packets_transmitted: data.total_packets_transmitted(),
+        received_per_sec: rate(data.received(), elapsed),
+        transmitted_per_sec: rate(data.transmitted(), elapsed),
+        packets_received_per_sec: rate(data.packets_received(), elapsed),
+        packets_transmitted_per_sec: rate(data.packets_transmitted(), elapsed),
+    }).collect();
+    networks.sort_by(|a, b| a.name.cmp(&b.name));
+    SystemOverview {
+        hostname: System::host_name(),
+        kernel_version: System::kernel_version(),
+        os_version: System::long_os_version(),
+        uptime: System::uptime(),
+        boot_time: System::boot_time(),
+        cpu_usage: system.global_cpu_usage(),
+        cpus: system.cpus().iter().map(|cpu| CpuCore {
+            name: cpu.name().to_string(),
+            cpu_usage: cpu.cpu_usage(),
+            frequency: cpu.frequency(),
+        }).collect(),
+        load_average: LoadAverage { one: load.one, five: load.five, fifteen: load.fifteen },
+        total_memory: system.total_memory(),
+        used_memory: system.used_memory(),
+        available_memory: system.available_memory(),
+        free_memory: system.free_memory(),
+        total_swap: system.total_swap(),
+        used_swap: system.used_swap(),
+        disks,
+        networks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(rate(1000, Some(Duration::from_secs(2))), Some(500.0));
+        assert_eq!(rate(1000, None), None);
+        assert_eq!(rate(1000, Some(Duration::ZERO)), None);
+    }
+
+    #[test]
+    fn test_collect() {
+        let system = System::new_all();
+        let overview = collect(&system, &Disks::new_with_refreshed_list(), &Networks::new_with_refreshed_list(), None);
+        assert!(!overview.cpus.is_empty());
+        assert!(overview.total_memory > 0);
+        assert!(overview.networks.iter().all(|n| n.received_per_sec.is_none()));
+    }
+}
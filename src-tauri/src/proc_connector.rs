@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::io;
+use std::mem::{size_of, zeroed};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::thread;
+use std::time::Duration;
+
+use crate::exec_events::{now_millis, ExecDetails, ExecParent, ExecSource, SharedExecTracker};
+use crate::monitor::{ProcessExit, SharedExitReports};
+use crate::procfs;
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long `recv` blocks before the thread checks whether it was disabled.
+const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
+const CN_IDX_PROC: u32 = 0x1;
+const CN_VAL_PROC: u32 = 0x1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+
+const PROC_EVENT_NONE: u32 = 0x0;
+const PROC_EVENT_FORK: u32 = 0x1;
+const PROC_EVENT_EXEC: u32 = 0x2;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+const NLMSG_HDRLEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+/// what, cpu and timestamp_ns precede the event data in `struct proc_event`.
+const PROC_EVENT_HDRLEN: usize = 16;
+
+#[derive(Debug, PartialEq)]
+pub enum ProcEvent {
+    Fork { parent_tgid: u32, child_pid: u32, child_tgid: u32 },
+    Exec { pid: u32, tgid: u32 },
+    Exit { pid: u32, tgid: u32, exit_code: u32 },
+    Ack { err: u32 },
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    buf.get(offset..offset + 4).map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn parse_event(buf: &[u8], offset: usize) -> Option<ProcEvent> {
+    let ev = offset + NLMSG_HDRLEN + CN_MSG_LEN;
+    let data = ev + PROC_EVENT_HDRLEN;
+    match read_u32(buf, ev)? {
+        PROC_EVENT_FORK => Some(ProcEvent::Fork {
+            parent_tgid: read_u32(buf, data + 4)?,
+            child_pid: read_u32(buf, data + 8)?,
+            child_tgid: read_u32(buf, data + 12)?,
+        }),
+        PROC_EVENT_EXEC => Some(ProcEvent::Exec {
+            pid: read_u32(buf, data)?,
+            tgid: read_u32(buf, data + 4)?,
+        }),
+        PROC_EVENT_EXIT => Some(ProcEvent::Exit {
+            pid: read_u32(buf, data)?,
+            tgid: read_u32(buf, data + 4)?,
+            exit_code: read_u32(buf, data + 8)?,
+        }),
+        PROC_EVENT_NONE => Some(ProcEvent::Ack { err: read_u32(buf, data)? }),
+        _ => None,
+    }
+}
+
+/// Parses every `proc_event` in a datagram received from the connector.
+pub fn parse_events(buf: &[u8]) -> Vec<ProcEvent> {
+    let mut events = vec![];
+    let mut offset = 0;
+    while let Some(len) = read_u32(buf, offset) {
+        let len = len as usize;
+        if len < NLMSG_HDRLEN || offset + len > buf.len() {
+            break;
+        }
+        events.extend(parse_event(buf, offset));
+        offset += (len + 3) & !3;
+    }
+    events
+}
+
+/// Turns a raw wait status into a shell-style exit code (128 + signal when killed).
+pub fn decode_wait_status(status: u32) -> i32 {
+    let signal = status & 0x7f;
+    if signal == 0 {
+        ((status >> 8) & 0xff) as i32
+    } else {
+        128 + signal as i32
+    }
+}
+
+pub struct ProcConnector {
+    fd: OwnedFd,
+}
+
+impl ProcConnector {
+    /// Subscribes to process events; fails with `EPERM` without `CAP_NET_ADMIN`.
+    pub fn open() -> io::Result<Self> {
+        let raw = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_CONNECTOR) };
+        if raw < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+        let mut addr: libc::sockaddr_nl = unsafe { zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = CN_IDX_PROC;
+        let ret = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let timeout = libc::timeval { tv_sec: RECV_TIMEOUT.as_secs() as libc::time_t, tv_usec: 0 };
+        let ret = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let connector = Self { fd };
+        connector.send_op(PROC_CN_MCAST_LISTEN)?;
+        Ok(connector)
+    }
+
+    fn send_op(&self, op: u32) -> io::Result<()> {
+        let total = NLMSG_HDRLEN + CN_MSG_LEN + size_of::<u32>();
+        let mut msg = Vec::with_capacity(total);
+        // struct nlmsghdr
+        msg.extend_from_slice(&(total as u32).to_ne_bytes());
+        msg.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        msg.extend_from_slice(&0u16.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&std::process::id().to_ne_bytes());
+        // struct cn_msg
+        msg.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        msg.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&(size_of::<u32>() as u16).to_ne_bytes());
+        msg.extend_from_slice(&0u16.to_ne_bytes());
+        // enum proc_cn_mcast_op
+        msg.extend_from_slice(&op.to_ne_bytes());
+
+        let ret = unsafe { libc::send(self.fd.as_raw_fd(), msg.as_ptr() as *const libc::c_void, msg.len(), 0) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<Vec<ProcEvent>> {
+        let ret = unsafe { libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(parse_events(&buf[..ret as usize]))
+    }
+}
+
+fn parent_chain(mut ppid: u32) -> Vec<ExecParent> {
+    let mut parents = vec![];
+    while ppid != 0 && parents.len() < 64 {
+        match procfs::read_stat(ppid) {
+            Some(stat) => {
+                parents.push(ExecParent { pid: ppid, name: Some(stat.comm) });
+                ppid = stat.ppid;
+            }
+            None => {
+                parents.push(ExecParent { pid: ppid, name: None });
+                break;
+            }
+        }
+    }
+    parents
+}
+
+fn read_details(pid: u32) -> ExecDetails {
+    let ppid = procfs::read_stat(pid).map(|s| s.ppid);
+    ExecDetails {
+        name: procfs::read_comm(pid),
+        cmdline: procfs::read_cmdline(pid),
+        exe: procfs::read_exe(pid),
+        parents: ppid.map(parent_chain).unwrap_or_default(),
+    }
+}
+
+fn report_exit(tracker: &SharedExecTracker, reports: &SharedExitReports, pid: u32, exit_code: Option<i32>) {
+    let event = tracker.lock().unwrap().exited(pid, exit_code, now_millis());
+    let mut reports = reports.lock().unwrap();
+    if let Some(code) = exit_code {
+        reports.codes.insert(pid, code);
+    }
+    if let Some(event) = event {
+        reports.unseen.push(ProcessExit::from(&event));
+    }
+}
+
+/// Runs until the config disables it (`Ok`) or the connector fails.
+fn run_connector(connector: ProcConnector, tracker: &SharedExecTracker, reports: &SharedExitReports) -> io::Result<()> {
+    let mut buf = vec![0u8; 8192];
+    loop {
+        if !tracker.lock().unwrap().keep_running() {
+            return Ok(());
+        }
+        let events = match connector.recv(&mut buf) {
+            Ok(events) => events,
+            // the socket buffer overflowed and we lost some events, exits among them
+            Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => {
+                let pids: HashSet<u32> = procfs::list_pids().into_iter().collect();
+                tracker.lock().unwrap().prune(&pids);
+                continue;
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock) => continue,
+            Err(e) => return Err(e),
+        };
+        for event in events {
+            match event {
+                ProcEvent::Fork { parent_tgid, child_pid, child_tgid } if child_pid == child_tgid => {
+                    let details = ExecDetails {
+                        name: procfs::read_comm(child_pid),
+                        parents: parent_chain(parent_tgid),
+                        ..ExecDetails::default()
+                    };
+                    tracker.lock().unwrap().started(child_pid, Some(parent_tgid), details, now_millis());
+                }
+                ProcEvent::Exec { pid, tgid } if pid == tgid => {
+                    let details = read_details(pid);
+                    tracker.lock().unwrap().exec(pid, details, now_millis());
+                }
+                ProcEvent::Exit { pid, tgid, exit_code } if pid == tgid => {
+                    report_exit(tracker, reports, pid, Some(decode_wait_status(exit_code)));
+                }
+                ProcEvent::Ack { err } if err != 0 => {
+                    return Err(io::Error::from_raw_os_error(err as i32));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn run_polling(tracker: &SharedExecTracker, reports: &SharedExitReports) {
+    let mut known: HashSet<u32> = procfs::list_pids().into_iter().collect();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if !tracker.lock().unwrap().keep_running() {
+            return;
+        }
+        let current: HashSet<u32> = procfs::list_pids().into_iter().collect();
+        for pid in current.difference(&known) {
+            let details = read_details(*pid);
+            tracker.lock().unwrap().exec(*pid, details, now_millis());
+        }
+        for pid in known.difference(&current) {
+            report_exit(tracker, reports, *pid, None);
+        }
+        known = current;
+    }
+}
+
+/// Starts the exec event source on a background thread, falling back to polling `/proc`;
+/// the thread ends once `ExecConfig::enabled` is turned off.
+pub fn spawn(tracker: SharedExecTracker, reports: SharedExitReports) {
+    thread::spawn(move || {
+        let err = match ProcConnector::open() {
+            Ok(connector) => {
+                tracker.lock().unwrap().set_source(ExecSource::ProcConnector);
+                match run_connector(connector, &tracker, &reports) {
+                    Ok(()) => return,
+                    Err(e) => Some(e),
+                }
+            }
+            Err(e) => Some(e),
+        };
+        if let Some(e) = err {
+            tracing::warn!("proc connector unavailable, polling /proc instead: {}", e);
+        }
+        tracker.lock().unwrap().set_source(ExecSource::Polling);
+        run_polling(&tracker, &reports);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(what: u32, data: &[u32]) -> Vec<u8> {
+        let len = NLMSG_HDRLEN + CN_MSG_LEN + PROC_EVENT_HDRLEN + data.len() * 4;
+        let mut buf = vec![0u8; NLMSG_HDRLEN + CN_MSG_LEN];
+        buf[..4].copy_from_slice(&(len as u32).to_ne_bytes());
+        buf.extend_from_slice(&what.to_ne_bytes());
+        buf.extend_from_slice(&[0u8; 12]);
+        data.iter().for_each(|d| buf.extend_from_slice(&d.to_ne_bytes()));
+        buf
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let mut buf = message(PROC_EVENT_FORK, &[1, 1, 42, 42]);
+        buf.extend(message(PROC_EVENT_EXIT, &[42, 42, 256, 17]));
+        assert_eq!(parse_events(&buf), vec![
+            ProcEvent::Fork { parent_tgid: 1, child_pid: 42, child_tgid: 42 },
+            ProcEvent::Exit { pid: 42, tgid: 42, exit_code: 256 },
+        ]);
+    }
+
+    #[test]
+    fn test_decode_wait_status() {
+        assert_eq!(decode_wait_status(0), 0);
+        assert_eq!(decode_wait_status(1 << 8), 1);
+        assert_eq!(decode_wait_status(9), 137);
+    }
+}
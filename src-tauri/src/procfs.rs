@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// The fields of `/proc/<pid>/stat` we care about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub comm: String,
+    pub state: char,
+    pub ppid: u32,
+    /// Clock ticks since boot.
+    pub starttime: u64,
+}
+
+pub fn proc_path(pid: u32, file: &str) -> PathBuf {
+    PathBuf::from(format!("/proc/{}/{}", pid, file))
+}
+
+pub fn list_pids() -> Vec<u32> {
+    match fs::read_dir("/proc") {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str().and_then(|s| s.parse::<u32>().ok()))
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+pub fn parse_stat(content: &str) -> Option<ProcStat> {
+    // comm is wrapped in parentheses and may itself contain spaces or parentheses
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let pid = content[..open].trim().parse().ok()?;
+    let comm = content[open + 1..close].to_string();
+    let rest: Vec<&str> = content[close + 1..].split_whitespace().collect();
+    let state = rest.first()?.chars().next()?;
+    let ppid = rest.get(1)?.parse().ok()?;
+    let starttime = rest.get(19)?.parse().ok()?;
+    Some(ProcStat { pid, comm, state, ppid, starttime })
+}
+
+pub fn read_stat(pid: u32) -> Option<ProcStat> {
+    let content = fs::read_to_string(proc_path(pid, "stat")).ok()?;
+    parse_stat(&content)
+}
+
+pub fn read_comm(pid: u32) -> Option<String> {
+    fs::read_to_string(proc_path(pid, "comm")).ok().map(|s| s.trim_end().to_string())
+}
+
+pub fn read_cmdline(pid: u32) -> Option<String> {
+    let bytes = fs::read(proc_path(pid, "cmdline")).ok()?;
+    if bytes.is_empty() {
+        return None;
+    }
+    let args: Vec<String> = bytes
+        .split(|b| *b == 0)
+        .filter(|a| !a.is_empty())
+        .map(|a| String::from_utf8_lossy(a).to_string())
+        .collect();
+    Some(args.join(" "))
+}
+
+pub fn read_exe(pid: u32) -> Option<String> {
+    fs::read_link(proc_path(pid, "exe")).ok().map(|p| p.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = "1234 (my (odd) proc) S 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 0 1 0 98765 1000 200";
+        assert_eq!(parse_stat(stat), Some(ProcStat {
+            pid: 1234,
+            comm: "my (odd) proc".to_string(),
+            state: 'S',
+            ppid: 1,
+            starttime: 98765,
+        }));
+    }
+
+    #[test]
+    fn test_read_self() {
+        let pid = std::process::id();
+        assert_eq!(read_stat(pid).map(|s| s.pid), Some(pid));
+        assert!(read_exe(pid).is_some());
+    }
+}
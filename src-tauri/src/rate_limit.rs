@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Sustained `/emit` requests per second allowed for each client.
+pub const RATE_PER_SEC: f64 = 5.0;
+/// Requests a client may send at once before being limited.
+pub const BURST: f64 = 10.0;
+
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per client address; every request takes one token, refilled at `rate` per second.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: HashMap<IpAddr, TokenBucket>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RATE_PER_SEC, BURST)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64, burst: f64) -> Self {
+        Self { rate, burst, buckets: HashMap::new() }
+    }
+
+    /// Takes a token for `client`, or returns how long until one is available.
+    pub fn check(&mut self, client: IpAddr, now: Instant) -> std::result::Result<(), Duration> {
+        let (rate, burst) = (self.rate, self.burst);
+        // a full bucket is the same as no bucket, so idle clients are forgotten
+        self.buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst);
+        let bucket = self.buckets.entry(client).or_insert(TokenBucket { tokens: burst, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut limiter = RateLimiter::new(2.0, 3.0);
+        let start = Instant::now();
+        let a: IpAddr = "127.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+        for _ in 0..3 {
+            assert!(limiter.check(a, start).is_ok());
+        }
+        let retry = limiter.check(a, start).unwrap_err();
+        assert_eq!(retry, Duration::from_millis(500));
+        // other clients have their own bucket
+        assert!(limiter.check(b, start).is_ok());
+        assert!(limiter.check(a, start + Duration::from_millis(500)).is_ok());
+        assert!(limiter.check(a, start + Duration::from_millis(500)).is_err());
+        // idle clients are dropped once their bucket refilled
+        assert!(limiter.check(b, start + Duration::from_secs(10)).is_ok());
+        assert!(!limiter.buckets.contains_key(&a));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use tokio::sync::{watch, RwLock};
+
+use crate::AppState;
+use crate::error::{ApiError, Result};
+use crate::exec_events::now_millis;
+use crate::monitor::SAMPLE_INTERVAL;
+use crate::sys::ProcessInfo;
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "remotes.json";
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+pub const BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+pub const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Another tr-process instance serving the HTTP API.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct RemoteHost {
+    pub name: String,
+    /// Base URL, e.g. `http://10.0.0.5:9090`.
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RemoteConfig {
+    pub hosts: Vec<RemoteHost>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RemoteStatus {
+    Connecting,
+    Connected,
+    Backoff,
+    Stopped,
+}
+
+/// Payload of the `remote-state` event.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct RemoteState {
+    pub name: String,
+    pub url: String,
+    pub status: RemoteStatus,
+    pub last_error: Option<String>,
+    /// Milliseconds since the unix epoch of the last successful poll.
+    pub last_seen: Option<u64>,
+    pub process_count: Option<u32>,
+    /// Milliseconds until the next attempt while in `Backoff`.
+    pub retry_in: Option<u64>,
+}
+
+struct RemoteEntry {
+    host: RemoteHost,
+    state: RemoteState,
+    processes: Vec<ProcessInfo>,
+    stop_tx: Option<watch::Sender<bool>>,
+}
+
+#[derive(Default)]
+pub struct Remotes {
+    hosts: HashMap<String, RemoteEntry>,
+}
+
+impl Remotes {
+    pub fn states(&self) -> Vec<RemoteState> {
+        let mut states: Vec<RemoteState> = self.hosts.values().map(|e| e.state.clone()).collect();
+        states.sort_by(|a, b| a.name.cmp(&b.name));
+        states
+    }
+
+    pub fn config(&self) -> RemoteConfig {
+        let mut hosts: Vec<RemoteHost> = self.hosts.values().map(|e| e.host.clone()).collect();
+        hosts.sort_by(|a, b| a.name.cmp(&b.name));
+        RemoteConfig { hosts }
+    }
+
+    /// Last snapshot of every connected host, labelled with the host name.
+    pub fn processes(&self) -> Vec<ProcessInfo> {
+        self.hosts.values()
+            .flat_map(|e| e.processes.iter().map(|p| ProcessInfo { host: Some(e.host.name.clone()), ..p.clone() }))
+            .collect()
+    }
+
+    fn insert(&mut self, host: RemoteHost) {
+        let state = RemoteState {
+            name: host.name.clone(),
+            url: host.url.clone(),
+            status: RemoteStatus::Stopped,
+            last_error: None,
+            last_seen: None,
+            process_count: None,
+            retry_in: None,
+        };
+        self.hosts.insert(host.name.clone(), RemoteEntry { host, state, processes: Vec::new(), stop_tx: None });
+    }
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<RemoteConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(RemoteConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &RemoteConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+fn backoff(failures: u32) -> Duration {
+    BACKOFF_INITIAL.saturating_mul(1u32 << failures.min(16)).min(BACKOFF_MAX)
+}
+
+/// Applies `f` to the host's entry, emitting `remote-state` when its status or error changed.
+async fn update(app_state: &Arc<RwLock<AppState>>, name: &str, f: impl FnOnce(&mut RemoteEntry)) {
+    let state = app_state.read().await;
+    let mut remotes = state.remotes.lock().await;
+    if let Some(entry) = remotes.hosts.get_mut(name) {
+        let before = (entry.state.status.clone(), entry.state.last_error.clone());
+        f(entry);
+        if before != (entry.state.status.clone(), entry.state.last_error.clone()) {
+            state.emit("remote-state", entry.state.clone());
+        }
+    }
+}
+
+async fn fetch_processes(client: &reqwest::Client, host: &RemoteHost) -> Result<Vec<ProcessInfo>> {
+    let url = format!("{}/api/v1/processes", host.url.trim_end_matches('/'));
+    let mut req = client.get(url);
+    if let Some(token) = host.token.as_ref() {
+        req = req.bearer_auth(token);
+    }
+    let res = req.send().await?.error_for_status()?;
+    Ok(res.json().await?)
+}
+
+async fn run_remote(app_state: Arc<RwLock<AppState>>, host: RemoteHost, mut stop_rx: watch::Receiver<bool>) {
+    let name = host.name.clone();
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            update(&app_state, &name, |e2| {
+                e2.state.status = RemoteStatus::Stopped;
+                e2.state.last_error = Some(e.to_string());
+            }).await;
+            return;
+        }
+    };
+    update(&app_state, &name, |e| e.state.status = RemoteStatus::Connecting).await;
+
+    let mut failures = 0;
+    loop {
+        let delay = match fetch_processes(&client, &host).await {
+            Ok(processes) => {
+                failures = 0;
+                update(&app_state, &name, |e| {
+                    e.state.status = RemoteStatus::Connected;
+                    e.state.last_error = None;
+                    e.state.last_seen = Some(now_millis());
+                    e.state.process_count = Some(processes.len() as u32);
+                    e.state.retry_in = None;
+                    e.processes = processes;
+                }).await;
+                SAMPLE_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff(failures);
+                failures += 1;
+                update(&app_state, &name, |e| {
+                    e.state.status = RemoteStatus::Backoff;
+                    e.state.last_error = Some(err.to_string());
+                    e.state.retry_in = Some(delay.as_millis() as u64);
+                    e.processes.clear();
+                }).await;
+                delay
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stop_rx.changed() => {
+                update(&app_state, &name, |e| {
+                    e.state.status = RemoteStatus::Stopped;
+                    e.state.retry_in = None;
+                    e.processes.clear();
+                }).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn connect(app_state: &Arc<RwLock<AppState>>, name: &str) -> Result<()> {
+    let remotes = app_state.read().await.remotes.clone();
+    let mut remotes = remotes.lock().await;
+    let entry = remotes.hosts.get_mut(name).ok_or(ApiError::NotFound(format!("No remote host named {}", name).into()))?;
+    let (stop_tx, stop_rx) = watch::channel(false);
+    entry.stop_tx = Some(stop_tx);
+    tokio::spawn(run_remote(app_state.clone(), entry.host.clone(), stop_rx));
+    Ok(())
+}
+
+pub async fn add_remote(app_state: &Arc<RwLock<AppState>>, host: RemoteHost) -> Result<()> {
+    let remotes = app_state.read().await.remotes.clone();
+    let name = host.name.clone();
+    {
+        let mut remotes = remotes.lock().await;
+        if remotes.hosts.contains_key(&name) {
+            return Err(ApiError::Conflict(format!("Remote host {} already exists", name).into()));
+        }
+        remotes.insert(host);
+        save_config(&remotes.config())?;
+    }
+    connect(app_state, &name).await
+}
+
+pub async fn remove_remote(app_state: &Arc<RwLock<AppState>>, name: &str) -> Result<()> {
+    let remotes = app_state.read().await.remotes.clone();
+    let mut remotes = remotes.lock().await;
+    let entry = remotes.hosts.remove(name).ok_or(ApiError::NotFound(format!("No remote host named {}", name).into()))?;
+    if let Some(stop_tx) = entry.stop_tx {
+        let _ = stop_tx.send(true);
+    }
+    save_config(&remotes.config())
+}
+
+/// Local processes followed by those of every connected remote host.
+pub async fn merged_processes(app_state: &Arc<RwLock<AppState>>) -> Vec<ProcessInfo> {
+    let (monitor, remotes) = {
+        let state = app_state.read().await;
+        (state.monitor.clone(), state.remotes.clone())
+    };
+    let mut processes = monitor.lock().await.processes();
+    processes.extend(remotes.lock().await.processes());
+    processes
+}
+
+/// Loads the persisted hosts and starts polling all of them.
+pub async fn run(app_state: Arc<RwLock<AppState>>) -> Result<()> {
+    let config = load_config()?;
+    let remotes = app_state.read().await.remotes.clone();
+    let names: Vec<String> = {
+        let mut remotes = remotes.lock().await;
+        config.hosts.into_iter().map(|host| {
+            let name = host.name.clone();
+            remotes.insert(host);
+            name
+        }).collect()
+    };
+    for name in names {
+        connect(&app_state, &name).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_processes_are_labelled() {
+        let mut remotes = Remotes::default();
+        remotes.insert(RemoteHost { name: "vm".to_string(), url: "http://vm:9090".to_string(), token: None });
+        remotes.hosts.get_mut("vm").unwrap().processes = vec![ProcessInfo { pid: 1, ..ProcessInfo::default() }];
+        let processes = remotes.processes();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].host.as_deref(), Some("vm"));
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(0), BACKOFF_INITIAL);
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(30), BACKOFF_MAX);
+    }
+}
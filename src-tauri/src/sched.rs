@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::error::{ApiError, Result};
+
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum IoPriorityClass {
+    None,
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IoPriority {
+    pub class: IoPriorityClass,
+    /// 0 (highest) to 7 for `RealTime` and `BestEffort`.
+    pub level: u8,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct SchedInfo {
+    pub pid: u32,
+    pub nice: Option<i32>,
+    pub affinity: Option<Vec<u32>>,
+    pub io_priority: Option<IoPriority>,
+}
+
+fn check_nice(nice: i32) -> Result<()> {
+    if !(NICE_MIN..=NICE_MAX).contains(&nice) {
+        return Err(ApiError::InvalidArgument(format!("nice {} is outside {}..={}", nice, NICE_MIN, NICE_MAX).into()));
+    }
+    Ok(())
+}
+
+pub fn get_sched_info(pid: u32) -> Result<SchedInfo> {
+    let nice = get_priority(pid)?;
+    Ok(SchedInfo {
+        pid,
+        nice: Some(nice),
+        affinity: get_affinity(pid).ok(),
+        io_priority: get_io_priority(pid).ok(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::mem::size_of;
+
+    use super::{check_nice, IoPriority, IoPriorityClass};
+    use crate::error::{ApiError, Result};
+    use crate::procfs::proc_path;
+
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: u32 = 13;
+
+    /// Thread ids of `pid`; nice, affinity and ioprio are per thread on Linux.
+    fn tasks(pid: u32) -> Result<Vec<u32>> {
+        let entries = std::fs::read_dir(proc_path(pid, "task"))
+            .map_err(|_| ApiError::ProcessNotFound("No such process".into()).with_pid(pid))?;
+        Ok(entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str().and_then(|s| s.parse().ok()))
+            .collect())
+    }
+
+    pub fn get_priority(pid: u32) -> Result<i32> {
+        // -1 is a valid nice value, so errors are told apart through errno
+        unsafe { *libc::__errno_location() = 0 };
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+        if nice == -1 && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+            return Err(ApiError::last_os_error(pid).with_operation("getpriority"));
+        }
+        Ok(nice)
+    }
+
+    pub fn set_priority(pid: u32, nice: i32) -> Result<()> {
+        check_nice(nice)?;
+        for tid in tasks(pid)? {
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) } == -1 {
+                return Err(ApiError::last_os_error(pid).with_operation("setpriority"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_affinity(pid: u32) -> Result<Vec<u32>> {
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        if unsafe { libc::sched_getaffinity(pid as libc::pid_t, size_of::<libc::cpu_set_t>(), &mut set) } == -1 {
+            return Err(ApiError::last_os_error(pid).with_operation("sched_getaffinity"));
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+            .map(|cpu| cpu as u32)
+            .collect())
+    }
+
+    pub fn set_affinity(pid: u32, cpus: &[u32]) -> Result<()> {
+        if cpus.is_empty() {
+            return Err(ApiError::InvalidArgument("at least one cpu is required".into()));
+        }
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in cpus {
+            if *cpu as usize >= libc::CPU_SETSIZE as usize {
+                return Err(ApiError::InvalidArgument(format!("no cpu {}", cpu).into()));
+            }
+            unsafe { libc::CPU_SET(*cpu as usize, &mut set) };
+        }
+        for tid in tasks(pid)? {
+            if unsafe { libc::sched_setaffinity(tid as libc::pid_t, size_of::<libc::cpu_set_t>(), &set) } == -1 {
+                return Err(ApiError::last_os_error(pid).with_operation("sched_setaffinity"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_io_priority(pid: u32) -> Result<IoPriority> {
+        let value = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_int) };
+        if value == -1 {
+            return Err(ApiError::last_os_error(pid).with_operation("ioprio_get"));
+        }
+        let value = value as u32;
+        let class = match value >> IOPRIO_CLASS_SHIFT {
+            1 => IoPriorityClass::RealTime,
+            2 => IoPriorityClass::BestEffort,
+            3 => IoPriorityClass::Idle,
+            _ => IoPriorityClass::None,
+        };
+        Ok(IoPriority { class, level: (value & 0xff) as u8 })
+    }
+
+    pub fn set_io_priority(pid: u32, priority: &IoPriority) -> Result<()> {
+        if priority.level > 7 {
+            return Err(ApiError::InvalidArgument(format!("io priority level {} is outside 0..=7", priority.level).into()));
+        }
+        let class: u32 = match priority.class {
+            IoPriorityClass::None => 0,
+            IoPriorityClass::RealTime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        };
+        let value = (class << IOPRIO_CLASS_SHIFT) | priority.level as u32;
+        for tid in tasks(pid)? {
+            let ret = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid as libc::c_int, value as libc::c_int) };
+            if ret == -1 {
+                return Err(ApiError::last_os_error(pid).with_operation("ioprio_set"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use windows::Win32::Foundation::{CloseHandle, E_ACCESSDENIED, E_INVALIDARG, HANDLE};
+    use windows::Win32::System::Threading::{
+        GetPriorityClass, GetProcessAffinityMask, OpenProcess, SetPriorityClass, SetProcessAffinityMask,
+        ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS, PROCESS_ACCESS_RIGHTS, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION,
+        REALTIME_PRIORITY_CLASS,
+    };
+
+    use super::{check_nice, IoPriority};
+    use crate::error::{ApiError, Result};
+
+    fn map_error(pid: u32, e: windows::core::Error) -> ApiError {
+        let code = e.code();
+        match ApiError::from(e).with_pid(pid) {
+            ApiError::WindowsError(detail) if code == E_ACCESSDENIED => ApiError::PermissionDenied(detail),
+            // what OpenProcess reports for a pid that doesn't exist
+            ApiError::WindowsError(detail) if code == E_INVALIDARG => ApiError::ProcessNotFound(detail),
+            err => err,
+        }
+    }
+
+    fn with_process<T>(pid: u32, access: PROCESS_ACCESS_RIGHTS, f: impl FnOnce(HANDLE) -> windows::core::Result<T>) -> Result<T> {
+        let handle = unsafe { OpenProcess(access, false, pid) }.map_err(|e| map_error(pid, e))?;
+        let ret = f(handle).map_err(|e| map_error(pid, e));
+        let _ = unsafe { CloseHandle(handle) };
+        ret
+    }
+
+    pub fn get_priority(pid: u32) -> Result<i32> {
+        let class = with_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, |h| Ok(unsafe { GetPriorityClass(h) }))?;
+        Ok(match class {
+            c if c == IDLE_PRIORITY_CLASS.0 => 19,
+            c if c == BELOW_NORMAL_PRIORITY_CLASS.0 => 10,
+            c if c == ABOVE_NORMAL_PRIORITY_CLASS.0 => -5,
+            c if c == HIGH_PRIORITY_CLASS.0 => -10,
+            c if c == REALTIME_PRIORITY_CLASS.0 => -20,
+            _ => 0,
+        })
+    }
+
+    pub fn set_priority(pid: u32, nice: i32) -> Result<()> {
+        check_nice(nice)?;
+        // realtime is never chosen; it can starve the system
+        let class = match nice {
+            n if n <= -10 => HIGH_PRIORITY_CLASS,
+            n if n < 0 => ABOVE_NORMAL_PRIORITY_CLASS,
+            0 => NORMAL_PRIORITY_CLASS,
+            n if n < 15 => BELOW_NORMAL_PRIORITY_CLASS,
+            _ => IDLE_PRIORITY_CLASS,
+        };
+        with_process(pid, PROCESS_SET_INFORMATION, |h| unsafe { SetPriorityClass(h, class) })
+    }
+
+    pub fn get_affinity(pid: u32) -> Result<Vec<u32>> {
+        let mut process_mask = 0usize;
+        let mut system_mask = 0usize;
+        with_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, |h| unsafe {
+            GetProcessAffinityMask(h, &mut process_mask, &mut system_mask)
+        })?;
+        Ok((0..usize::BITS).filter(|cpu| process_mask & (1 << cpu) != 0).collect())
+    }
+
+    pub fn set_affinity(pid: u32, cpus: &[u32]) -> Result<()> {
+        if cpus.is_empty() {
+            return Err(ApiError::InvalidArgument("at least one cpu is required".into()));
+        }
+        let mut mask = 0usize;
+        for cpu in cpus {
+            if *cpu >= usize::BITS {
+                return Err(ApiError::InvalidArgument(format!("no cpu {}", cpu).into()));
+            }
+            mask |= 1 << cpu;
+        }
+        with_process(pid, PROCESS_SET_INFORMATION, |h| unsafe { SetProcessAffinityMask(h, mask) })
+    }
+
+    pub fn get_io_priority(_pid: u32) -> Result<IoPriority> {
+        Err(ApiError::Unsupported("I/O priority is only supported on Linux".into()))
+    }
+
+    pub fn set_io_priority(_pid: u32, _priority: &IoPriority) -> Result<()> {
+        Err(ApiError::Unsupported("I/O priority is only supported on Linux".into()))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod imp {
+    use super::IoPriority;
+    use crate::error::{ApiError, Result};
+
+    fn unsupported<T>() -> Result<T> {
+        Err(ApiError::Unsupported("Not supported on this platform".into()))
+    }
+
+    pub fn get_priority(_pid: u32) -> Result<i32> {
+        unsupported()
+    }
+
+    pub fn set_priority(_pid: u32, _nice: i32) -> Result<()> {
+        unsupported()
+    }
+
+    pub fn get_affinity(_pid: u32) -> Result<Vec<u32>> {
+        unsupported()
+    }
+
+    pub fn set_affinity(_pid: u32, _cpus: &[u32]) -> Result<()> {
+        unsupported()
+    }
+
+    pub fn get_io_priority(_pid: u32) -> Result<IoPriority> {
+        unsupported()
+    }
+
+    pub fn set_io_priority(_pid: u32, _priority: &IoPriority) -> Result<()> {
+        unsupported()
+    }
+}
+
+pub use imp::{get_affinity, get_io_priority, get_priority, set_affinity, set_io_priority, set_priority};
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_sched_info() {
+        let pid = std::process::id();
+        let info = get_sched_info(pid).unwrap();
+        assert!(info.nice.is_some());
+        assert!(!info.affinity.unwrap().is_empty());
+        set_priority(pid, info.nice.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(set_priority(std::process::id(), 42), Err(ApiError::InvalidArgument(_))));
+        assert!(matches!(get_priority(u32::MAX / 2), Err(ApiError::ProcessNotFound(_))));
+        assert!(matches!(set_affinity(std::process::id(), &[]), Err(ApiError::InvalidArgument(_))));
+    }
+}
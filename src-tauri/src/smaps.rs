@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+
+use crate::sys::ProcessInfo;
+
+/// `smaps_rollup` makes the kernel walk every mapping, so it is read less often than the sampler runs.
+pub const SMAPS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Memory breakdown from `/proc/<pid>/smaps_rollup`, in bytes.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MemoryDetail {
+    pub rss: u64,
+    /// Proportional set size: shared pages divided among the processes mapping them.
+    pub pss: u64,
+    /// Unique set size: pages mapped by this process only.
+    pub uss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub swap: u64,
+    pub swap_pss: u64,
+    pub anonymous: u64,
+    /// Resident pages backed by files, including shared memory.
+    pub file: u64,
+}
+
+/// Totals for a process and all of its descendants.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SubtreeMemory {
+    pub pid: u32,
+    pub process_count: u32,
+    pub rss: u64,
+    /// Sum of PSS, which doesn't double-count shared pages; falls back to `memory` without a breakdown.
+    pub pss: u64,
+    pub uss: u64,
+    pub swap: u64,
+}
+
+pub fn parse_smaps_rollup(content: &str) -> MemoryDetail {
+    let values: HashMap<&str, u64> = content.lines()
+        .filter_map(|line| {
+            let (key, rest) = line.split_once(':')?;
+            let kb: u64 = rest.split_whitespace().next()?.parse().ok()?;
+            Some((key, kb * 1024))
+        })
+        .collect();
+    let get = |key: &str| values.get(key).copied().unwrap_or_default();
+    let rss = get("Rss");
+    let anonymous = get("Anonymous");
+    MemoryDetail {
+        rss,
+        pss: get("Pss"),
+        uss: get("Private_Clean") + get("Private_Dirty"),
+        shared_clean: get("Shared_Clean"),
+        shared_dirty: get("Shared_Dirty"),
+        private_clean: get("Private_Clean"),
+        private_dirty: get("Private_Dirty"),
+        swap: get("Swap"),
+        swap_pss: get("SwapPss"),
+        anonymous,
+        file: rss.saturating_sub(anonymous),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<MemoryDetail> {
+    let content = std::fs::read_to_string(crate::procfs::proc_path(pid, "smaps_rollup")).ok()?;
+    Some(parse_smaps_rollup(&content))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<MemoryDetail> {
+    None
+}
+
+/// PSS when the breakdown is known, otherwise sysinfo's resident memory.
+pub fn pss_or_memory(p: &ProcessInfo) -> u64 {
+    p.memory_detail.as_ref().map(|m| m.pss).or(p.memory).unwrap_or_default()
+}
+
+pub fn subtree(processes: &[ProcessInfo], pid: u32) -> SubtreeMemory {
+    let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+    for p in processes.iter() {
+        if let Some(ppid) = p.ppid {
+            children.entry(ppid).or_default().push(p);
+        }
+    }
+    let mut total = SubtreeMemory { pid, ..SubtreeMemory::default() };
+    let mut stack: Vec<&ProcessInfo> = processes.iter().filter(|p| p.pid == pid).collect();
+    while let Some(p) = stack.pop() {
+        total.process_count += 1;
+        total.pss += pss_or_memory(p);
+        if let Some(m) = p.memory_detail.as_ref() {
+            total.rss += m.rss;
+            total.uss += m.uss;
+            total.swap += m.swap;
+        } else {
+            total.rss += p.memory.unwrap_or_default();
+        }
+        if let Some(kids) = children.get(&p.pid) {
+            // pid 0 is its own parent on some platforms
+            stack.extend(kids.iter().filter(|k| k.pid != p.pid));
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_smaps_rollup() {
+        let content = "\
+55d0c8a00000-7ffd1d9f5000 ---p 00000000 00:00 0                          [rollup]
+Rss:                9000 kB
+Pss:                4000 kB
+Shared_Clean:       5000 kB
+Shared_Dirty:        500 kB
+Private_Clean:       500 kB
+Private_Dirty:      3000 kB
+Anonymous:          3200 kB
+Swap:                 12 kB
+SwapPss:               6 kB
+";
+        let m = parse_smaps_rollup(content);
+        assert_eq!(m.rss, 9000 * 1024);
+        assert_eq!(m.pss, 4000 * 1024);
+        assert_eq!(m.uss, 3500 * 1024);
+        assert_eq!(m.file, 5800 * 1024);
+        assert_eq!(m.swap_pss, 6 * 1024);
+    }
+
+    #[test]
+    fn test_subtree_uses_pss() {
+        let detail = |pss: u64| Some(MemoryDetail { rss: 100, pss, ..MemoryDetail::default() });
+        let processes = vec![
+            ProcessInfo { pid: 1, memory: Some(100), memory_detail: detail(40), ..ProcessInfo::default() },
+            ProcessInfo { pid: 2, ppid: Some(1), memory: Some(100), memory_detail: detail(30), ..ProcessInfo::default() },
+            ProcessInfo { pid: 3, ppid: Some(2), memory: Some(70), ..ProcessInfo::default() },
+            ProcessInfo { pid: 4, memory: Some(999), ..ProcessInfo::default() },
+        ];
+        let total = subtree(&processes, 1);
+        assert_eq!(total.process_count, 3);
+        assert_eq!(total.pss, 140);
+        assert_eq!(total.rss, 270);
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use tokio::sync::{watch, RwLock};
+
+use crate::AppState;
+use crate::child::{self, ChildStatus, SpawnRequest};
+use crate::error::{ApiError, Result};
+use crate::sys::{ProcessInfo, SockState};
+use crate::utils::get_config_path;
+
+pub const CONFIG_FILE: &str = "supervisor.json";
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+/// Consecutive failed health checks before the process is killed and restarted.
+pub const HEALTH_CHECK_FAILURES: u32 = 3;
+/// A run lasting this long resets the backoff delay.
+pub const STABLE_RUN: Duration = Duration::from_secs(30);
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RestartPolicy {
+    Always,
+    OnFailure,
+    Never,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub enum HealthCheck {
+    /// Healthy while the process or one of its descendants listens on the port.
+    TcpPort { port: u16 },
+    /// Healthy while a GET returns a 2xx status.
+    Http { url: String },
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ServiceDef {
+    pub name: String,
+    pub spawn: SpawnRequest,
+    pub restart: RestartPolicy,
+    pub health_check: Option<HealthCheck>,
+    /// First restart delay in milliseconds, doubled on every consecutive failure.
+    pub backoff_initial: Option<u64>,
+    pub backoff_max: Option<u64>,
+}
+
+impl ServiceDef {
+    fn backoff(&self, failures: u32) -> Duration {
+        let initial = self.backoff_initial.unwrap_or(1_000);
+        let max = self.backoff_max.unwrap_or(60_000);
+        let delay = initial.saturating_mul(1u64 << failures.min(16));
+        Duration::from_millis(delay.min(max))
+    }
+}
+
+/// Whether a service that exited with `exit_code` is started again; `None` means it was killed or never started.
+fn should_restart(policy: &RestartPolicy, exit_code: Option<i32>) -> bool {
+    match policy {
+        RestartPolicy::Always => true,
+        RestartPolicy::OnFailure => exit_code != Some(0),
+        RestartPolicy::Never => false,
+    }
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SupervisorConfig {
+    pub services: Vec<ServiceDef>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ServiceStatus {
+    Starting,
+    Running,
+    Backoff,
+    Stopped,
+    /// Exited and the restart policy says to leave it down.
+    Exited,
+    Failed,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct ServiceState {
+    pub name: String,
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_exit_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub healthy: Option<bool>,
+    /// Milliseconds until the next restart while in `Backoff`.
+    pub restart_in: Option<u64>,
+}
+
+struct ServiceEntry {
+    def: ServiceDef,
+    state: ServiceState,
+    stop_tx: Option<watch::Sender<bool>>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    services: HashMap<String, ServiceEntry>,
+}
+
+impl Supervisor {
+    pub fn states(&self) -> Vec<ServiceState> {
+        let mut states: Vec<ServiceState> = self.services.values().map(|e| e.state.clone()).collect();
+        states.sort_by(|a, b| a.name.cmp(&b.name));
+        states
+    }
+
+    pub fn config(&self) -> SupervisorConfig {
+        let mut services: Vec<ServiceDef> = self.services.values().map(|e| e.def.clone()).collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        SupervisorConfig { services }
+    }
+
+    fn insert(&mut self, def: ServiceDef) {
+        let state = ServiceState {
+            name: def.name.clone(),
+            status: ServiceStatus::Stopped,
+            pid: None,
+            restarts: 0,
+            last_exit_code: None,
+            last_error: None,
+            healthy: None,
+            restart_in: None,
+        };
+        self.services.insert(def.name.clone(), ServiceEntry { def, state, stop_tx: None });
+    }
+
+    fn get_mut(&mut self, name: &str) -> Result<&mut ServiceEntry> {
+        self.services.get_mut(name).ok_or(ApiError::NotFound(format!("No service named {}", name).into()))
+    }
+}
+
+fn config_file() -> Result<PathBuf> {
+    Ok(get_config_path()?.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<SupervisorConfig> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(SupervisorConfig::default());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_config(config: &SupervisorConfig) -> Result<()> {
+    let path = config_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+async fn update_state(app_state: &Arc<RwLock<AppState>>, name: &str, f: impl FnOnce(&mut ServiceState)) {
+    let state = app_state.read().await;
+    let mut supervisor = state.supervisor.lock().await;
+    if let Some(entry) = supervisor.services.get_mut(name) {
+        f(&mut entry.state);
+        state.emit("service-state", entry.state.clone());
+    }
+}
+
+fn descendants(processes: &[ProcessInfo], pid: u32) -> HashSet<u32> {
+    let mut pids = HashSet::from([pid]);
+    loop {
+        let before = pids.len();
+        for p in processes.iter() {
+            if p.ppid.is_some_and(|ppid| pids.contains(&ppid)) {
+                pids.insert(p.pid);
+            }
+        }
+        if pids.len() == before {
+            return pids;
+        }
+    }
+}
+
+async fn check_health(app_state: &Arc<RwLock<AppState>>, check: &HealthCheck, pid: u32) -> bool {
+    match check {
+        HealthCheck::TcpPort { port } => {
+            let monitor = app_state.read().await.monitor.clone();
+            let processes = monitor.lock().await.processes();
+            let pids = descendants(&processes, pid);
+            processes.iter()
+                .filter(|p| pids.contains(&p.pid))
+                .flat_map(|p| p.socks.iter())
+                .any(|s| s.local_port == *port && matches!(s.state, Some(SockState::Listen)))
+        }
+        HealthCheck::Http { url } => {
+            let client = match reqwest::Client::builder().timeout(HEALTH_CHECK_TIMEOUT).build() {
+                Ok(client) => client,
+                Err(_) => return false,
+            };
+            match client.get(url).send().await {
+                Ok(res) => res.status().is_success(),
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+/// Resolves once the health check has failed `HEALTH_CHECK_FAILURES` times in a row.
+async fn watch_health(app_state: &Arc<RwLock<AppState>>, name: &str, check: Option<HealthCheck>, pid: u32) {
+    let Some(check) = check else {
+        return std::future::pending().await;
+    };
+    let mut failures = 0;
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let healthy = check_health(app_state, &check, pid).await;
+        update_state(app_state, name, |s| s.healthy = Some(healthy)).await;
+        failures = if healthy { 0 } else { failures + 1 };
+        if failures >= HEALTH_CHECK_FAILURES {
+            return;
+        }
+    }
+}
+
+async fn run_service(app_state: Arc<RwLock<AppState>>, def: ServiceDef, mut stop_rx: watch::Receiver<bool>) {
+    let name = def.name.clone();
+    let mut failures = 0;
+    loop {
+        update_state(&app_state, &name, |s| {
+            s.status = ServiceStatus::Starting;
+            s.restart_in = None;
+        }).await;
+        let started = Instant::now();
+        let exit_code = match child::spawn(&app_state, def.spawn.clone()).await {
+            Ok(spawned) => {
+                let pid = spawned.pid;
+                update_state(&app_state, &name, |s| {
+                    s.status = ServiceStatus::Running;
+                    s.pid = Some(pid);
+                    s.healthy = None;
+                    s.last_error = None;
+                }).await;
+                let status = tokio::select! {
+                    status = child::wait(&app_state, pid) => status,
+                    _ = stop_rx.changed() => {
+                        let _ = child::kill(&app_state, pid).await;
+                        child::wait(&app_state, pid).await
+                    }
+                    _ = watch_health(&app_state, &name, def.health_check.clone(), pid) => {
+                        update_state(&app_state, &name, |s| s.last_error = Some("health check failed".to_string())).await;
+                        let _ = child::kill(&app_state, pid).await;
+                        child::wait(&app_state, pid).await
+                    }
+                };
+                match status {
+                    Ok(ChildStatus::Exited { exit_code }) => exit_code,
+                    _ => None,
+                }
+            }
+            Err(e) => {
+                update_state(&app_state, &name, |s| s.last_error = Some(e.to_string())).await;
+                None
+            }
+        };
+        update_state(&app_state, &name, |s| {
+            s.pid = None;
+            s.last_exit_code = exit_code;
+        }).await;
+
+        if *stop_rx.borrow() {
+            update_state(&app_state, &name, |s| s.status = ServiceStatus::Stopped).await;
+            return;
+        }
+        if !should_restart(&def.restart, exit_code) {
+            let status = if exit_code == Some(0) { ServiceStatus::Exited } else { ServiceStatus::Failed };
+            update_state(&app_state, &name, |s| s.status = status).await;
+            return;
+        }
+
+        if started.elapsed() >= STABLE_RUN {
+            failures = 0;
+        }
+        let delay = def.backoff(failures);
+        failures += 1;
+        update_state(&app_state, &name, |s| {
+            s.status = ServiceStatus::Backoff;
+            s.restarts += 1;
+            s.restart_in = Some(delay.as_millis() as u64);
+        }).await;
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stop_rx.changed() => {
+                update_state(&app_state, &name, |s| {
+                    s.status = ServiceStatus::Stopped;
+                    s.restart_in = None;
+                }).await;
+                return;
+            }
+        }
+    }
+}
+
+pub async fn start(app_state: &Arc<RwLock<AppState>>, name: &str) -> Result<()> {
+    let supervisor = app_state.read().await.supervisor.clone();
+    let mut supervisor = supervisor.lock().await;
+    let entry = supervisor.get_mut(name)?;
+    if matches!(entry.state.status, ServiceStatus::Starting | ServiceStatus::Running | ServiceStatus::Backoff) {
+        return Err(ApiError::Conflict(format!("Service {} is already running", name).into()));
+    }
+    let (stop_tx, stop_rx) = watch::channel(false);
+    entry.stop_tx = Some(stop_tx);
+    entry.state.restarts = 0;
+    tokio::spawn(run_service(app_state.clone(), entry.def.clone(), stop_rx));
+    Ok(())
+}
+
+pub async fn stop(app_state: &Arc<RwLock<AppState>>, name: &str) -> Result<()> {
+    let supervisor = app_state.read().await.supervisor.clone();
+    let mut supervisor = supervisor.lock().await;
+    let entry = supervisor.get_mut(name)?;
+    if let Some(stop_tx) = entry.stop_tx.as_ref() {
+        let _ = stop_tx.send(true);
+    }
+    Ok(())
+}
+
+pub async fn add_service(app_state: &Arc<RwLock<AppState>>, def: ServiceDef) -> Result<()> {
+    let supervisor = app_state.read().await.supervisor.clone();
+    let name = def.name.clone();
+    {
+        let mut supervisor = supervisor.lock().await;
+        if supervisor.services.contains_key(&name) {
+            return Err(ApiError::Conflict(format!("Service {} already exists", name).into()));
+        }
+        supervisor.insert(def);
+        save_config(&supervisor.config())?;
+    }
+    start(app_state, &name).await
+}
+
+pub async fn remove_service(app_state: &Arc<RwLock<AppState>>, name: &str) -> Result<()> {
+    stop(app_state, name).await?;
+    let supervisor = app_state.read().await.supervisor.clone();
+    let mut supervisor = supervisor.lock().await;
+    supervisor.services.remove(name);
+    save_config(&supervisor.config())
+}
+
+/// Loads the persisted service definitions and starts all of them.
+pub async fn run(app_state: Arc<RwLock<AppState>>) -> Result<()> {
+    let config = load_config()?;
+    let supervisor = app_state.read().await.supervisor.clone();
+    let names: Vec<String> = {
+        let mut supervisor = supervisor.lock().await;
+        config.services.into_iter().map(|def| {
+            let name = def.name.clone();
+            supervisor.insert(def);
+            name
+        }).collect()
+    };
+    for name in names {
+        // one broken service shouldn't keep the others from starting
+        if let Err(e) = start(&app_state, &name).await {
+            tracing::error!(service = %name, "failed to start service: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(backoff_initial: Option<u64>, backoff_max: Option<u64>) -> ServiceDef {
+        ServiceDef {
+            name: "web".to_string(),
+            spawn: SpawnRequest { cmd: "true".to_string(), ..SpawnRequest::default() },
+            restart: RestartPolicy::Always,
+            health_check: None,
+            backoff_initial,
+            backoff_max,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let defaults = service(None, None);
+        assert_eq!(defaults.backoff(0), Duration::from_secs(1));
+        assert_eq!(defaults.backoff(1), Duration::from_secs(2));
+        assert_eq!(defaults.backoff(5), Duration::from_secs(32));
+        // capped at the 60s default
+        assert_eq!(defaults.backoff(6), Duration::from_secs(60));
+
+        let custom = service(Some(100), Some(1_000));
+        assert_eq!(custom.backoff(3), Duration::from_millis(800));
+        assert_eq!(custom.backoff(4), Duration::from_millis(1_000));
+
+        // the shift stops at 16 doublings instead of overflowing
+        let uncapped = service(Some(1), Some(u64::MAX));
+        assert_eq!(uncapped.backoff(16), Duration::from_millis(1 << 16));
+        assert_eq!(uncapped.backoff(u32::MAX), Duration::from_millis(1 << 16));
+    }
+
+    #[test]
+    fn test_should_restart() {
+        let cases = [
+            (RestartPolicy::Always, Some(0), true),
+            (RestartPolicy::Always, Some(1), true),
+            (RestartPolicy::Always, None, true),
+            (RestartPolicy::OnFailure, Some(0), false),
+            (RestartPolicy::OnFailure, Some(1), true),
+            (RestartPolicy::OnFailure, None, true),
+            (RestartPolicy::Never, Some(0), false),
+            (RestartPolicy::Never, Some(1), false),
+            (RestartPolicy::Never, None, false),
+        ];
+        for (policy, exit_code, expected) in cases {
+            assert_eq!(should_restart(&policy, exit_code), expected, "{:?} {:?}", policy, exit_code);
+        }
+    }
+}
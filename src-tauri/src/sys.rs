@@ -1,5 +1,7 @@
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(windows)]
 use std::mem::size_of;
 // use std::{thread};
 
@@ -7,8 +9,11 @@ use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSock
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none};
 use specta::Type;
-use sysinfo::{DiskUsage, Process, System};
+use sysinfo::{DiskUsage, Pid, Process, System, ThreadKind};
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{ProcessesToUpdate, Signal};
 
+#[cfg(windows)]
 use windows::{
     Win32::System::ProcessStatus::EnumProcesses,
     Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ, GetProcessTimes},
@@ -16,7 +21,12 @@ use windows::{
 };
 
 
-use crate::error::Result;
+use crate::cgroup::{self, CgroupInfo};
+use crate::diskio;
+use crate::error::{ApiError, Result};
+use crate::integrity::ExeIntegrity;
+use crate::namespace::{self, Namespaces};
+use crate::smaps::MemoryDetail;
 
 
 #[skip_serializing_none]
@@ -79,13 +89,15 @@ impl From<&TcpState> for SockState {
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
 pub struct SockInfo {
-    local_addr: String,
-    local_port: u16,
-    protocol: SockProtocol,
-    pids: Vec<u32>,
-    remote_addr: Option<String>,
-    remote_port: Option<u16>,
-    state: Option<SockState>,
+    pub local_addr: String,
+    pub local_port: u16,
+    pub protocol: SockProtocol,
+    pub pids: Vec<u32>,
+    pub remote_addr: Option<String>,
+    pub remote_port: Option<u16>,
+    pub state: Option<SockState>,
+    /// Network namespace inode, for sockets outside tr-process's own namespace.
+    pub netns: Option<u64>,
 }
 
 impl From<&SocketInfo> for SockInfo {
@@ -120,6 +132,7 @@ impl From<&SocketInfo> for SockInfo {
             remote_addr,
             remote_port,
             state,
+            netns: None,
         }
 
     }
@@ -132,15 +145,16 @@ impl From<&SocketInfo> for SockInfo {
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct ProcessInfo {
-    pid: u32,
-    ppid: Option<u32>,
-    name: Option<String>,
-    exe: Option<String>,
-    cpu_usage: Option<f32>,
-    memory: Option<u64>,
-    disk_usage: Option<DiskInfo>,
-    accumulated_cpu_time: Option<u64>,
-    socks: Vec<SockInfo>,
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: Option<String>,
+    pub exe: Option<String>,
+    pub cpu_usage: Option<f32>,
+    pub memory: Option<u64>,
+    pub open_files: Option<u64>,
+    pub disk_usage: Option<DiskInfo>,
+    pub accumulated_cpu_time: Option<u64>,
+    pub socks: Vec<SockInfo>,
     // local_addr: Option<String>,
     // local_port: Option<u16>,
     // protocol: Option<SockProtocol>,
@@ -148,18 +162,47 @@ pub struct ProcessInfo {
     // remote_port: Option<u16>,
     // state: Option<SockState>,
 
-    uptime: Option<u64>,
+    pub uptime: Option<u64>,
+    pub start_time: Option<u64>,
+    /// Set for children started through `spawn_process`.
+    pub spawned: Option<bool>,
+    /// Breakdown from `smaps_rollup`, refreshed every `SMAPS_INTERVAL`.
+    pub memory_detail: Option<MemoryDetail>,
+    pub cgroup: Option<CgroupInfo>,
+    /// sysinfo's status, e.g. `Zombie`.
+    pub status: Option<String>,
+    /// Stand-in for a parent pid that isn't in the snapshot; every other field is unset.
+    pub missing: Option<bool>,
+    /// Set by the sampler's integrity pass.
+    pub integrity: Option<ExeIntegrity>,
+    pub namespaces: Option<Namespaces>,
+    /// Name of the remote host the process runs on; `None` for this machine.
+    pub host: Option<String>,
 }
 
 
 #[skip_serializing_none]
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
-struct DiskInfo {
-    read_bytes: u64,
-    write_bytes: u64,
-    total_read_bytes: u64,
-    total_write_bytes: u64,
+pub struct DiskInfo {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub total_read_bytes: u64,
+    pub total_write_bytes: u64,
+    /// Read/write syscalls from `/proc/<pid>/io`.
+    pub read_syscalls: Option<u64>,
+    pub write_syscalls: Option<u64>,
+    /// Bytes written and then truncated away before reaching the disk.
+    pub cancelled_write_bytes: Option<u64>,
+    /// Syscall rates over the last sampler tick.
+    pub read_syscalls_per_sec: Option<f64>,
+    pub write_syscalls_per_sec: Option<f64>,
+}
+
+impl ProcessInfo {
+    pub fn is_missing(&self) -> bool {
+        self.missing == Some(true)
+    }
 }
 
 impl From<&DiskUsage> for DiskInfo {
@@ -169,6 +212,11 @@ impl From<&DiskUsage> for DiskInfo {
             write_bytes: disk_usage.written_bytes,
             total_read_bytes: disk_usage.total_read_bytes,
             total_write_bytes: disk_usage.total_written_bytes,
+            read_syscalls: None,
+            write_syscalls: None,
+            cancelled_write_bytes: None,
+            read_syscalls_per_sec: None,
+            write_syscalls_per_sec: None,
         }
     }
 }
@@ -181,9 +229,17 @@ fn make_process_info(process: &Process, sockets: &Vec<SockInfo>, uptime: Option<
     let exe = process.exe().map(|p|p.to_string_lossy().to_string());
     let cpu_usage = Some(process.cpu_usage());
     let memory = Some(process.memory());
-    let disk_usage = Some(DiskInfo::from(&process.disk_usage()));
+    let open_files = process.open_files().map(|n| n as u64);
+    let mut disk_usage = DiskInfo::from(&process.disk_usage());
+    if let Some(io) = diskio::read_process_io(pid) {
+        disk_usage.read_syscalls = Some(io.syscr);
+        disk_usage.write_syscalls = Some(io.syscw);
+        disk_usage.cancelled_write_bytes = Some(io.cancelled_write_bytes);
+    }
+    let disk_usage = Some(disk_usage);
     let accumulated_cpu_time = Some(process.accumulated_cpu_time());
     let ppid = process.parent().map(|p| p.as_u32());
+    let start_time = Some(process.start_time());
     let socks = sockets.iter().filter(|s| s.pids.contains(&pid)).cloned().collect();
     // let (
     //     local_addr, local_port, protocol, remote_addr, remote_port, state
@@ -209,6 +265,7 @@ fn make_process_info(process: &Process, sockets: &Vec<SockInfo>, uptime: Option<
         exe,
         cpu_usage,
         memory,
+        open_files,
         disk_usage,
         accumulated_cpu_time,
         socks,
@@ -218,31 +275,60 @@ fn make_process_info(process: &Process, sockets: &Vec<SockInfo>, uptime: Option<
         // remote_addr,
         // remote_port,
         // state,
-        uptime
+        uptime,
+        start_time,
+        spawned: None,
+        memory_detail: None,
+        status: Some(process.status().to_string()),
+        missing: None,
+        integrity: None,
+        cgroup: cgroup::read(pid),
+        namespaces: namespace::read(pid),
+        host: None,
     }
 }
 
 
 
 pub fn get_processes_map() -> Result<HashMap<u32, ProcessInfo>> {
+    let mut system = System::new_all();
+    system.refresh_all();
+    // thread::sleep(Duration::from_secs(1));
+    // system.refresh_all();
+    collect_processes(&system)
+}
+
+pub fn get_sockets() -> Result<Vec<SockInfo>> {
     let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
     let proto_flags = ProtocolFlags::TCP | ProtocolFlags::UDP;
     let sockets_info = get_sockets_info(af_flags, proto_flags)?;
-    let sockets: Vec<SockInfo> = sockets_info.iter().map(SockInfo::from).collect();
+    let mut sockets: Vec<SockInfo> = sockets_info.iter().map(SockInfo::from).collect();
+    sockets.extend(namespace::foreign_sockets());
+    Ok(sockets)
+}
+
+pub fn collect_processes(system: &System) -> Result<HashMap<u32, ProcessInfo>> {
+    collect_processes_with(system, &get_sockets()?)
+}
+
+pub fn collect_processes_with(system: &System, sockets: &[SockInfo]) -> Result<HashMap<u32, ProcessInfo>> {
+    #[cfg(windows)]
     let uptimes = get_process_uptime()?;
 
-    let mut system = System::new_all();
-    system.refresh_all();
-    // thread::sleep(Duration::from_secs(1));
-    // system.refresh_all();
-    let processes = system.processes();
+    // on Linux sysinfo also lists every userland thread as a process
+    let processes: HashMap<&Pid, &Process> = system.processes().iter()
+        .filter(|(_, p)| !matches!(p.thread_kind(), Some(ThreadKind::Userland)))
+        .collect();
 
     let mut process_map: HashMap<u32, ProcessInfo> = processes.iter().map(|(k,v)| {
         let pid = k.as_u32();
         let sockets = sockets.iter().filter(|s| {
             s.pids.contains(&pid)
         }).map(|s| s.clone()).collect();
+        #[cfg(windows)]
         let find_uptime = uptimes.get(&pid).and_then(|u| u.uptime);
+        #[cfg(not(windows))]
+        let find_uptime = Some(v.run_time());
         (
             pid,
             make_process_info(v, &sockets, find_uptime)
@@ -261,6 +347,7 @@ pub fn get_processes_map() -> Result<HashMap<u32, ProcessInfo>> {
             let ppid = parent.as_u32();
             process_map.entry(ppid).or_insert(ProcessInfo {
                 pid: ppid,
+                missing: Some(true),
                 ..ProcessInfo::default()
             });
         }
@@ -269,6 +356,28 @@ pub fn get_processes_map() -> Result<HashMap<u32, ProcessInfo>> {
     // Ok(process_map.into_iter().map(|(_,v)| v).collect())
 }
 
+/// Asks the process to exit: SIGTERM on Unix, TerminateProcess on Windows.
+#[cfg(target_os = "linux")]
+pub fn kill_process(pid: u32) -> Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == -1 {
+        return Err(ApiError::last_os_error(pid).with_operation("kill"));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn kill_process(pid: u32) -> Result<()> {
+    let mut system = System::new();
+    let target = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[target]), true);
+    let process = system.process(target).ok_or_else(|| ApiError::ProcessNotFound("No such process".into()).with_pid(pid))?;
+    let killed = process.kill_with(Signal::Term).unwrap_or_else(|| process.kill());
+    if !killed {
+        return Err(ApiError::Error("Failed to kill the process".into()).with_operation("kill").with_pid(pid));
+    }
+    Ok(())
+}
+
 pub fn get_processes() -> Result<Vec<ProcessInfo>> {
     let process_map = get_processes_map()?;
     let process_vec: Vec<ProcessInfo> = process_map.into_iter().map(|(_,v)| v).collect();
@@ -280,6 +389,7 @@ pub fn get_processes() -> Result<Vec<ProcessInfo>> {
 
 
 
+#[cfg(windows)]
 #[skip_serializing_none]
 #[serde_as]
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
@@ -288,12 +398,14 @@ pub struct ProcessUptime {
     uptime: Option<u64>,
 }
 
+#[cfg(windows)]
 fn filetime_to_unix_epoch(ft: FILETIME) -> u64 {
     let high = (ft.dwHighDateTime as u64) << 32;
     let low = ft.dwLowDateTime as u64;
     let total = high | low;
     total / 10_000_000 - 11644473600
 }
+#[cfg(windows)]
 fn get_process_uptime() -> Result<HashMap<u32, ProcessUptime>> {
     let mut processes = [0u32; 1024];
     let mut cb_needed = 0;
@@ -305,7 +417,7 @@ fn get_process_uptime() -> Result<HashMap<u32, ProcessUptime>> {
     )}?;
 
     let num_processes = cb_needed as usize / size_of::<u32>();
-    println!("num_processes: {}", num_processes);
+    tracing::trace!(num_processes, "enumerated processes");
     let mut processes_uptime = HashMap::new();
     for i in 0..num_processes {
         let pid = processes[i];
@@ -317,12 +429,12 @@ fn get_process_uptime() -> Result<HashMap<u32, ProcessUptime>> {
             });
             continue;
         }
-        println!("pid: {:?}", pid);
+        tracing::trace!(pid, "opening process");
 
         let handle = match unsafe {OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)} {
             Ok(handle) => handle,
             Err(e) => {
-                println!("{:?}", e);
+                tracing::debug!(pid, "OpenProcess failed: {}", e);
                 processes_uptime.insert(pid, ProcessUptime {
                     pid,
                     uptime,
@@ -331,7 +443,7 @@ fn get_process_uptime() -> Result<HashMap<u32, ProcessUptime>> {
             }
         };
         if handle.is_invalid() {
-            println!("handle is_invalid()");
+            tracing::debug!(pid, "invalid process handle");
             processes_uptime.insert(pid, ProcessUptime {
                 pid,
                 uptime,
@@ -368,6 +480,14 @@ mod tests {
         assert!(matches!(get_processes(), Ok(_)));
     }
 
+    #[test]
+    fn test_collect_processes() {
+        let mut system = System::new_all();
+        system.refresh_all();
+        assert!(matches!(collect_processes(&system), Ok(_)));
+    }
+
+    #[cfg(windows)]
     #[test]
     fn test_get_process_uptime() {
         assert!(matches!(get_process_uptime(), Ok(_)));
@@ -1,6 +1,9 @@
 use std::env;
 use std::path::PathBuf;
 use crate::error::{Result, ApiError};
+
+pub const APP_IDENTIFIER: &str = "com.tr-process.app";
+
 pub fn get_resource_path() -> Result<PathBuf> {
     if tauri::is_dev() {
         let current_path = env::current_dir()?;
@@ -9,7 +12,29 @@ pub fn get_resource_path() -> Result<PathBuf> {
         let current_path = env::current_exe()?;
         let base_path = current_path
             .parent()
-            .ok_or(ApiError::Error("err parent".to_string()))?;
+            .ok_or(ApiError::Error("err parent".into()))?;
         Ok(base_path.join("resources"))
     }
 }
+
+pub fn get_config_path() -> Result<PathBuf> {
+    if tauri::is_dev() {
+        let current_path = env::current_dir()?;
+        Ok(current_path.join("config"))
+    } else {
+        let base_path = dirs::config_dir()
+            .ok_or(ApiError::Error("err config dir".into()))?;
+        Ok(base_path.join(APP_IDENTIFIER))
+    }
+}
+
+pub fn get_log_path() -> Result<PathBuf> {
+    if tauri::is_dev() {
+        let current_path = env::current_dir()?;
+        Ok(current_path.join("logs"))
+    } else {
+        let base_path = dirs::data_local_dir()
+            .ok_or(ApiError::Error("err data dir".into()))?;
+        Ok(base_path.join(APP_IDENTIFIER).join("logs"))
+    }
+}
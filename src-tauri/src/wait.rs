@@ -0,0 +1,240 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use specta::Type;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, ThreadKind};
+use tokio::sync::Mutex;
+
+use crate::error::Result;
+use crate::monitor::Monitor;
+use crate::sys::{self, ProcessInfo, SockInfo, SockProtocol, SockState};
+
+pub const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+pub const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest wait the server accepts, so a client can't hold a request open indefinitely.
+pub const MAX_WAIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub enum WaitCondition {
+    /// A TCP socket listening on the port, or a bound UDP socket.
+    PortListening { port: u16, protocol: Option<SockProtocol> },
+    /// A process whose name contains `name`, case-insensitively.
+    ProcessExists { name: String },
+    ProcessExited { pid: u32 },
+    NoProcess { name: String },
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct WaitRequest {
+    pub condition: WaitCondition,
+    pub timeout_ms: Option<u64>,
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Type, Serialize, Deserialize, Clone, Debug)]
+pub struct WaitResult {
+    pub satisfied: bool,
+    pub elapsed_ms: u64,
+    /// Processes that satisfied the condition, e.g. the listeners on the port.
+    pub pids: Vec<u32>,
+}
+
+fn name_matches(name: Option<&str>, pattern: &str) -> bool {
+    name.is_some_and(|n| n.to_lowercase().contains(&pattern.to_lowercase()))
+}
+
+/// Returns the matching pids when the condition holds.
+pub fn evaluate(condition: &WaitCondition, processes: &[ProcessInfo], sockets: &[SockInfo]) -> Option<Vec<u32>> {
+    // a zombie has exited, it just hasn't been reaped
+    let alive = |p: &&ProcessInfo| !p.is_missing() && p.status.as_deref() != Some("Zombie");
+    match condition {
+        WaitCondition::PortListening { port, protocol } => {
+            let listeners: Vec<&SockInfo> = sockets.iter()
+                .filter(|s| s.local_port == *port)
+                .filter(|s| match (&s.protocol, protocol) {
+                    (SockProtocol::Tcp, None | Some(SockProtocol::Tcp)) => matches!(s.state, Some(SockState::Listen)),
+                    (SockProtocol::Udp, None | Some(SockProtocol::Udp)) => true,
+                    _ => false,
+                })
+                .collect();
+            // the owner may be unknown without permission to read its fds
+            let mut pids: Vec<u32> = listeners.iter().flat_map(|s| s.pids.iter().copied()).collect();
+            pids.sort();
+            pids.dedup();
+            (!listeners.is_empty()).then_some(pids)
+        }
+        WaitCondition::ProcessExists { name } => {
+            let pids: Vec<u32> = processes.iter().filter(alive).filter(|p| name_matches(p.name.as_deref(), name)).map(|p| p.pid).collect();
+            (!pids.is_empty()).then_some(pids)
+        }
+        WaitCondition::ProcessExited { pid } => {
+            (!processes.iter().filter(alive).any(|p| p.pid == *pid)).then_some(vec![*pid])
+        }
+        WaitCondition::NoProcess { name } => {
+            (!processes.iter().filter(alive).any(|p| name_matches(p.name.as_deref(), name))).then_some(vec![])
+        }
+    }
+}
+
+/// Only what `evaluate` looks at, so polling stays cheap.
+fn list_processes(system: &mut System) -> Vec<ProcessInfo> {
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    system.processes().values()
+        .filter(|p| !matches!(p.thread_kind(), Some(ThreadKind::Userland)))
+        .map(|p| ProcessInfo {
+            pid: p.pid().as_u32(),
+            ppid: p.parent().map(|pid| pid.as_u32()),
+            name: Some(p.name().to_string_lossy().to_string()),
+            status: Some(p.status().to_string()),
+            ..ProcessInfo::default()
+        })
+        .collect()
+}
+
+impl WaitRequest {
+    fn timeout(&self) -> Duration {
+        self.timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_WAIT_TIMEOUT)
+    }
+}
+
+/// Polls every `WAIT_POLL_INTERVAL` until the condition holds or the timeout passes. Blocks the calling thread;
+/// for the CLI, which has no sampler to piggyback on.
+pub fn wait(request: &WaitRequest) -> Result<WaitResult> {
+    let timeout = request.timeout();
+    let started = Instant::now();
+    let mut system = System::new();
+    loop {
+        let sockets = match request.condition {
+            WaitCondition::PortListening { .. } => sys::get_sockets()?,
+            _ => vec![],
+        };
+        let processes = list_processes(&mut system);
+        let elapsed = started.elapsed();
+        if let Some(pids) = evaluate(&request.condition, &processes, &sockets) {
+            return Ok(WaitResult { satisfied: true, elapsed_ms: elapsed.as_millis() as u64, pids });
+        }
+        if elapsed >= timeout {
+            return Ok(WaitResult { satisfied: false, elapsed_ms: elapsed.as_millis() as u64, pids: vec![] });
+        }
+        thread::sleep(WAIT_POLL_INTERVAL.min(timeout - elapsed));
+    }
+}
+
+/// Re-checks the monitor's snapshot after each refresh until the condition holds or the timeout,
+/// capped at `MAX_WAIT_TIMEOUT`, passes.
+pub async fn wait_for(monitor: Arc<Mutex<Monitor>>, request: &WaitRequest) -> WaitResult {
+    let timeout = request.timeout().min(MAX_WAIT_TIMEOUT);
+    let started = Instant::now();
+    let mut refreshed = monitor.lock().await.subscribe();
+    loop {
+        let pids = {
+            let monitor = monitor.lock().await;
+            // an empty snapshot would satisfy ProcessExited and NoProcess
+            match monitor.is_sampled() {
+                true => evaluate(&request.condition, &monitor.processes(), &monitor.sockets()),
+                false => None,
+            }
+        };
+        let elapsed = started.elapsed();
+        if let Some(pids) = pids {
+            return WaitResult { satisfied: true, elapsed_ms: elapsed.as_millis() as u64, pids };
+        }
+        if elapsed >= timeout {
+            return WaitResult { satisfied: false, elapsed_ms: elapsed.as_millis() as u64, pids: vec![] };
+        }
+        let remaining = timeout - elapsed;
+        match tokio::time::timeout(remaining, refreshed.changed()).await {
+            Ok(Ok(())) | Err(_) => {}
+            // the monitor is gone, so nothing will change before the timeout
+            Ok(Err(_)) => tokio::time::sleep(remaining).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sock(port: u16, protocol: SockProtocol, state: Option<SockState>, pid: u32) -> SockInfo {
+        SockInfo {
+            local_addr: "0.0.0.0".to_string(),
+            local_port: port,
+            protocol,
+            pids: vec![pid],
+            remote_addr: None,
+            remote_port: None,
+            state,
+            netns: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let process = |pid: u32, name: &str, status: &str| ProcessInfo {
+            pid,
+            name: Some(name.to_string()),
+            status: Some(status.to_string()),
+            ..ProcessInfo::default()
+        };
+        let processes = vec![process(10, "postgres", "Sleeping"), process(11, "webpack", "Zombie")];
+        let sockets = vec![
+            sock(8080, SockProtocol::Tcp, Some(SockState::Established), 10),
+            sock(5432, SockProtocol::Tcp, Some(SockState::Listen), 10),
+            sock(53, SockProtocol::Udp, None, 10),
+        ];
+        let listening = |port: u16, protocol: Option<SockProtocol>| WaitCondition::PortListening { port, protocol };
+
+        assert_eq!(evaluate(&listening(5432, None), &processes, &sockets), Some(vec![10]));
+        assert_eq!(evaluate(&listening(8080, None), &processes, &sockets), None);
+        assert_eq!(evaluate(&listening(53, Some(SockProtocol::Udp)), &processes, &sockets), Some(vec![10]));
+        assert_eq!(evaluate(&listening(53, Some(SockProtocol::Tcp)), &processes, &sockets), None);
+        assert_eq!(evaluate(&WaitCondition::ProcessExists { name: "Postgres".to_string() }, &processes, &sockets), Some(vec![10]));
+        assert_eq!(evaluate(&WaitCondition::ProcessExited { pid: 11 }, &processes, &sockets), Some(vec![11]));
+        assert_eq!(evaluate(&WaitCondition::ProcessExited { pid: 10 }, &processes, &sockets), None);
+        assert!(evaluate(&WaitCondition::NoProcess { name: "webpack".to_string() }, &processes, &sockets).is_some());
+    }
+
+    #[test]
+    fn test_wait_times_out() {
+        let request = WaitRequest {
+            condition: WaitCondition::ProcessExited { pid: std::process::id() },
+            timeout_ms: Some(300),
+        };
+        let result = wait(&request).unwrap();
+        assert!(!result.satisfied);
+        assert!(result.elapsed_ms >= 300);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_refresh() {
+        let monitor = Arc::new(Mutex::new(Monitor::new()));
+        let request = WaitRequest {
+            condition: WaitCondition::NoProcess { name: "no-such-process".to_string() },
+            timeout_ms: Some(10_000),
+        };
+        let waiter = tokio::spawn({
+            let monitor = monitor.clone();
+            async move { wait_for(monitor, &request).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished(), "decided before the first sample");
+        monitor.lock().await.refresh().unwrap();
+        assert!(waiter.await.unwrap().satisfied);
+
+        let request = WaitRequest {
+            condition: WaitCondition::ProcessExited { pid: std::process::id() },
+            timeout_ms: Some(300),
+        };
+        let result = wait_for(monitor, &request).await;
+        assert!(!result.satisfied);
+        assert!(result.elapsed_ms >= 300);
+    }
+}
@@ -21,6 +21,393 @@ async getProcess() : Promise<Result<ProcessInfo[], ApiError>> {
     else return { status: "error", error: e  as any };
 }
 },
+async getCgroups() : Promise<Result<CgroupNode, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_cgroups") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCgroupStats(path: string) : Promise<Result<CgroupStats, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_cgroup_stats", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getNamespaces(kind: NamespaceKind) : Promise<Result<NamespaceGroup[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_namespaces", { kind }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSchedInfo(pid: number) : Promise<Result<SchedInfo, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sched_info", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPriority(pid: number, nice: number) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_priority", { pid, nice }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAffinity(pid: number) : Promise<Result<number[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_affinity", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAffinity(pid: number, cpus: number[]) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_affinity", { pid, cpus }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setIoPriority(pid: number, priority: IoPriority) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_io_priority", { pid, priority }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLimits(pid: number) : Promise<Result<ProcessLimits, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_limits", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLimit(pid: number, resource: Resource, soft: RlimitValue, hard: RlimitValue) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_limit", { pid, resource, soft, hard }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setOomScoreAdj(pid: number, value: number) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_oom_score_adj", { pid, value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMemoryDetail(pid: number) : Promise<Result<MemoryDetail, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_memory_detail", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSubtreeMemory(pid: number) : Promise<Result<SubtreeMemory, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_subtree_memory", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSystemOverview() : Promise<Result<SystemOverview, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_system_overview") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDiskIo() : Promise<Result<DeviceIo[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_disk_io") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getProcessAnomalies() : Promise<Result<ProcessAnomalies, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_process_anomalies") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLeakSuspects() : Promise<Result<LeakSuspect[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_leak_suspects") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLeakConfig() : Promise<Result<LeakConfig, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_leak_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLeakConfig(config: LeakConfig) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_leak_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExitLog() : Promise<Result<ProcessExit[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_exit_log") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async replyHttp(reply: HttpReply) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reply_http", { reply }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async killProcess(pid: number) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("kill_process", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLogs(level: LogLevel | null, since: number | null, limit: number | null) : Promise<Result<LogEntry[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_logs", { level, since, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAuditLog() : Promise<Result<AuditEntry[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_audit_log") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLogLevel() : Promise<Result<LogLevel, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_log_level") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLogLevel(level: LogLevel) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async execEvents() : Promise<Result<ExecEvents, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("exec_events") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExecConfig() : Promise<Result<ExecConfig, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_exec_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setExecConfig(config: ExecConfig) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_exec_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async spawnProcess(cmd: string, args: string[], cwd: string | null, env: Partial<{ [key in string]: string }> | null) : Promise<Result<SpawnedChild, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("spawn_process", { cmd, args, cwd, env }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async writeStdin(pid: number, data: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("write_stdin", { pid, data }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async killChild(pid: number) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("kill_child", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async waitChild(pid: number) : Promise<Result<ChildStatus, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("wait_child", { pid }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getChildren() : Promise<Result<SpawnedChild[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_children") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getServices() : Promise<Result<ServiceState[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_services") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addService(def: ServiceDef) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_service", { def }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeService(name: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_service", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startService(name: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_service", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopService(name: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_service", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMetricsConfig() : Promise<Result<MetricsConfig, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_metrics_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMetricsConfig(config: MetricsConfig) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_metrics_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIntegrityConfig() : Promise<Result<IntegrityConfig, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_integrity_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setIntegrityConfig(config: IntegrityConfig) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_integrity_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Processes whose executable was deleted, replaced or failed the allow/deny lists.
+ */
+async getIntegrityIssues() : Promise<Result<ProcessInfo[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_integrity_issues") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRemotes() : Promise<Result<RemoteState[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_remotes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addRemote(host: RemoteHost) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_remote", { host }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeRemote(name: string) : Promise<Result<null, ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_remote", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAllProcesses() : Promise<Result<ProcessInfo[], ApiError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_all_processes") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async runHttpServer(servInfo: ServInfo) : Promise<Result<ServInfo, ApiError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("run_http_server", { servInfo }) };
@@ -41,13 +428,444 @@ async runHttpServer(servInfo: ServInfo) : Promise<Result<ServInfo, ApiError>> {
 
 /** user-defined types **/
 
-export type ApiError = { Error: string } | { JsonError: string } | { TokioError: string } | { IoError: string } | { NetstatError: string } | { WindowsError: string }
-export type DiskInfo = { read_bytes: number; write_bytes: number; total_read_bytes: number; total_write_bytes: number }
-export type ProcessInfo = { pid: number; ppid?: number | null; name?: string | null; exe?: string | null; cpu_usage?: number | null; memory?: number | null; disk_usage?: DiskInfo | null; accumulated_cpu_time?: number | null; socks: SockInfo[]; uptime?: number | null }
+/**
+ * Serialized with the variant name as `code`, so both the HTTP API and Tauri commands
+ * hand the frontend `{ code, message, ... }` to match on.
+ */
+export type ApiError = ({ code: "Error" } & ErrorDetail) | ({ code: "JsonError" } & ErrorDetail) | ({ code: "TokioError" } & ErrorDetail) | ({ code: "IoError" } & ErrorDetail) | ({ code: "NetstatError" } & ErrorDetail) | ({ code: "WindowsError" } & ErrorDetail) | ({ code: "HttpError" } & ErrorDetail) | ({ code: "PermissionDenied" } & ErrorDetail) | ({ code: "ProcessNotFound" } & ErrorDetail) | ({ code: "InvalidArgument" } & ErrorDetail) | ({ code: "Unauthorized" } & ErrorDetail) | ({ code: "NotFound" } & ErrorDetail) | ({ code: "Conflict" } & ErrorDetail) | ({ code: "Unavailable" } & ErrorDetail) | ({ code: "Timeout" } & ErrorDetail) | ({ code: "AddressInUse" } & ErrorDetail) | ({ code: "Unsupported" } & ErrorDetail) | ({ code: "TooManyRequests" } & ErrorDetail)
+export type AuditEntry = { 
+/**
+ * Milliseconds since the Unix epoch.
+ */
+timestamp: number; 
+/**
+ * Client address, without the port.
+ */
+client?: string | null; method: string; route: string; 
+/**
+ * From `Content-Length`; unset for streamed bodies.
+ */
+payload_size?: number | null; status: number; latency_ms: number }
+export type CgroupInfo = { 
+/**
+ * cgroup v2 path relative to the mount, e.g. `/system.slice/docker-<id>.scope`.
+ */
+path: string; container_id?: string | null; runtime?: ContainerRuntime | null; 
+/**
+ * Innermost systemd `.service` or `.scope`.
+ */
+unit?: string | null; 
+/**
+ * Innermost systemd `.slice`.
+ */
+slice?: string | null }
+/**
+ * A cgroup with its direct members; the totals include every descendant group and
+ * `memory` sums PSS where known.
+ */
+export type CgroupNode = { path: string; container_id?: string | null; runtime?: ContainerRuntime | null; unit?: string | null; pids: number[]; process_count: number; cpu_usage: number; memory: number; children: CgroupNode[] }
+/**
+ * Limits, usage and pressure of one cgroup v2 group; `None` where the controller isn't enabled.
+ */
+export type CgroupStats = { path: string; memory_current?: number | null; memory_max?: Limit | null; memory_high?: Limit | null; cpu_max?: CpuMax | null; cpu_stat?: CpuStat | null; pids_current?: number | null; pids_max?: Limit | null; cpu_pressure?: Pressure | null; memory_pressure?: Pressure | null; io_pressure?: Pressure | null }
+/**
+ * Payload of the `child-exited` event.
+ */
+export type ChildExit = { pid: number; exit_code?: number | null }
+/**
+ * Payload of the `child-output` event, one per line.
+ */
+export type ChildOutput = { pid: number; stream: ChildStream; line: string }
+export type ChildStatus = "Running" | { Exited: { exit_code?: number | null } }
+export type ChildStream = "Stdout" | "Stderr"
+export type ContainerRuntime = "Docker" | "Containerd" | "Podman" | "Crio"
+export type CpuCore = { name: string; cpu_usage: number; 
+/**
+ * MHz
+ */
+frequency: number }
+export type CpuMax = { 
+/**
+ * Microseconds of CPU time per period; `Max` means unthrottled.
+ */
+quota: Limit; period: number }
+/**
+ * `cpu.stat`; times are in microseconds.
+ */
+export type CpuStat = { usage_usec?: number | null; user_usec?: number | null; system_usec?: number | null; nr_periods?: number | null; nr_throttled?: number | null; throttled_usec?: number | null; 
+/**
+ * Share of enforcement periods in which the group was throttled.
+ */
+throttled_percent?: number | null }
+export type DeviceIo = { name: string; total_read_bytes: number; total_write_bytes: number; 
+/**
+ * Requests currently in flight.
+ */
+in_flight: number; 
+/**
+ * Rates over the last sampler tick; unset until two samples were taken.
+ */
+read_bytes_per_sec?: number | null; write_bytes_per_sec?: number | null; reads_per_sec?: number | null; writes_per_sec?: number | null; 
+/**
+ * Average number of requests in flight over the tick.
+ */
+queue_depth?: number | null; 
+/**
+ * Percentage of the tick the device was busy.
+ */
+utilization?: number | null }
+export type DiskInfo = { read_bytes: number; write_bytes: number; total_read_bytes: number; total_write_bytes: number; 
+/**
+ * Read/write syscalls from `/proc/<pid>/io`.
+ */
+read_syscalls?: number | null; write_syscalls?: number | null; 
+/**
+ * Bytes written and then truncated away before reaching the disk.
+ */
+cancelled_write_bytes?: number | null; 
+/**
+ * Syscall rates over the last sampler tick.
+ */
+read_syscalls_per_sec?: number | null; write_syscalls_per_sec?: number | null }
+export type DiskUsage = { name: string; mount_point: string; file_system: string; kind: string; total_space: number; available_space: number; used_space: number; is_removable: boolean; is_read_only: boolean }
+export type ErrorDetail = { message: string; 
+/**
+ * What was being attempted, e.g. `kill` or `bind`.
+ */
+operation?: string | null; pid?: number | null; path?: string | null; port?: number | null; 
+/**
+ * errno on Unix, HRESULT on Windows.
+ */
+os_error?: number | null; 
+/**
+ * Underlying errors, outermost first.
+ */
+causes: string[] }
+export type ExeIntegrity = { state: ExeState; 
+/**
+ * Digest of the image the process is running, which may differ from the file on disk.
+ */
+sha256?: string | null; verdict?: ListVerdict | null }
+export type ExeState = "Intact" | 
+/**
+ * The path no longer exists.
+ */
+"Deleted" | 
+/**
+ * Another file now lives at the path, e.g. after a deploy.
+ */
+"Replaced" | 
+/**
+ * The running file was written to after the process started.
+ */
+"Modified"
+export type ExecConfig = { 
+/**
+ * Off by default: without `CAP_NET_ADMIN` the fallback rescans `/proc` many times a second.
+ */
+enabled: boolean }
+export type ExecEvent = { pid: number; ppid?: number | null; name?: string | null; cmdline?: string | null; exe?: string | null; 
+/**
+ * Nearest parent first, up to init.
+ */
+parents: ExecParent[]; 
+/**
+ * Milliseconds since the unix epoch.
+ */
+started_at: number; exited_at?: number | null; exit_code?: number | null; 
+/**
+ * Lifetime in milliseconds.
+ */
+duration?: number | null }
+export type ExecEvents = { source: ExecSource; events: ExecEvent[] }
+export type ExecParent = { pid: number; name?: string | null }
+export type ExecSource = 
+/**
+ * Fork/exec/exit events from the Linux netlink process connector.
+ */
+"ProcConnector" | 
+/**
+ * `/proc` scanning, used when the connector needs privileges we don't have.
+ */
+"Polling" | "Disabled" | "Unsupported"
+export type HealthCheck = 
+/**
+ * Healthy while the process or one of its descendants listens on the port.
+ */
+{ TcpPort: { port: number } } | 
+/**
+ * Healthy while a GET returns a 2xx status.
+ */
+{ Http: { url: string } }
+export type HttpCmd = "Refresh" | { SelectPid: { pid: number } } | 
+/**
+ * Selects the process owning a socket on the local port.
+ */
+{ FocusPort: { port: number } } | 
+/**
+ * Filters the table by name, pid or port; `None` clears it.
+ */
+{ ApplyFilter: { text?: string | null } } | { SwitchView: { view: ViewKind } } | 
+/**
+ * Replies with the processes and selection the window is showing.
+ */
+"ExportSnapshot" | { KillProcess: { pid: number } }
+export type HttpNotify = { 
+/**
+ * Protocol version of the sender; 1 when unset.
+ */
+version?: number | null; 
+/**
+ * Correlation id echoed in the reply; assigned by `/emit` when unset.
+ */
+id?: string | null; cmd: HttpCmd }
+/**
+ * The window's answer to an `HttpNotify`.
+ */
+export type HttpReply = { id: string; version: number; ok: boolean; result?: JsonValue | null; error?: string | null }
+export type IntegrityConfig = { 
+/**
+ * Hashing is off unless enabled; deleted and replaced binaries are flagged either way.
+ */
+enabled: boolean; 
+/**
+ * Hex SHA-256 digests of known-good executables. When non-empty, anything else is flagged.
+ */
+allow: string[]; 
+/**
+ * Hex SHA-256 digests that are always flagged.
+ */
+deny: string[] }
+export type IoPriority = { class: IoPriorityClass; 
+/**
+ * 0 (highest) to 7 for `RealTime` and `BestEffort`.
+ */
+level: number }
+export type IoPriorityClass = "None" | "RealTime" | "BestEffort" | "Idle"
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+export type LeakConfig = { 
+/**
+ * Processes younger than this are not judged.
+ */
+min_age_secs: number; min_samples: number; 
+/**
+ * Resident memory growth, in bytes per hour, above which a process is suspect.
+ */
+memory_slope: number; 
+/**
+ * Open file descriptor growth, per hour, above which a process is suspect.
+ */
+fd_slope: number; 
+/**
+ * Minimum r² of the linear fit, so that spiky but flat usage isn't flagged.
+ */
+min_r_squared: number }
+export type LeakResource = "Memory" | "Fds"
+export type LeakSuspect = { pid: number; name?: string | null; resource: LeakResource; 
+/**
+ * Growth per hour, in bytes for memory.
+ */
+slope: number; r_squared: number; current: number; 
+/**
+ * The cgroup `memory.max` or total memory, whichever is lower, for `Memory`; the soft `RLIMIT_NOFILE` for `Fds`.
+ */
+limit?: number | null; 
+/**
+ * Projected seconds until `limit` is reached at the current slope.
+ */
+seconds_to_limit?: number | null; samples: number; window_secs: number }
+export type Limit = "Max" | { Value: number }
+export type ListVerdict = "Allowed" | "Denied" | "Unlisted"
+export type LoadAverage = { one: number; five: number; fifteen: number }
+export type LogEntry = { seq: number; 
+/**
+ * Milliseconds since the Unix epoch.
+ */
+timestamp: number; level: LogLevel; target: string; message: string; fields: Partial<{ [key in string]: string }>; 
+/**
+ * Enclosing spans, outermost first, as `name{field=value}`.
+ */
+spans: string[] }
+export type LogLevel = "Error" | "Warn" | "Info" | "Debug" | "Trace"
+/**
+ * Memory breakdown from `/proc/<pid>/smaps_rollup`, in bytes.
+ */
+export type MemoryDetail = { rss: number; 
+/**
+ * Proportional set size: shared pages divided among the processes mapping them.
+ */
+pss: number; 
+/**
+ * Unique set size: pages mapped by this process only.
+ */
+uss: number; shared_clean: number; shared_dirty: number; private_clean: number; private_dirty: number; swap: number; swap_pss: number; anonymous: number; 
+/**
+ * Resident pages backed by files, including shared memory.
+ */
+file: number }
+export type MetricsConfig = { 
+/**
+ * Process names exported per process; `*` matches any run of characters.
+ */
+allow: string[] }
+export type MissingParent = { pid: number; children: number[]; 
+/**
+ * The parent was in the previous snapshot, so it exited while this one was taken.
+ */
+vanished: boolean; 
+/**
+ * Last known name, when it vanished.
+ */
+name?: string | null }
+export type NamespaceGroup = { kind: NamespaceKind; inode: number; pids: number[]; 
+/**
+ * Whether tr-process itself lives in this namespace.
+ */
+own: boolean }
+export type NamespaceKind = "Pid" | "Net" | "Mnt" | "Uts" | "Ipc" | "User"
+/**
+ * Namespace inodes of a process, as in `/proc/<pid>/ns/*`.
+ */
+export type Namespaces = { pid?: number | null; net?: number | null; mnt?: number | null; uts?: number | null; ipc?: number | null; user?: number | null }
+export type NetworkInterface = { name: string; mac_address: string; ip_addresses: string[]; total_received: number; total_transmitted: number; total_packets_received: number; total_packets_transmitted: number; 
+/**
+ * Rates over the last sampler tick; unset until two samples were taken.
+ */
+received_per_sec?: number | null; transmitted_per_sec?: number | null; packets_received_per_sec?: number | null; packets_transmitted_per_sec?: number | null }
+export type Orphan = { pid: number; name?: string | null; ppid: number; original_ppid: number; reaper: ReaperKind }
+/**
+ * Pressure stall information from a `*.pressure` file.
+ */
+export type Pressure = { some?: PressureLine | null; full?: PressureLine | null }
+export type PressureLine = { avg10: number; avg60: number; avg300: number; 
+/**
+ * Total stall time in microseconds.
+ */
+total: number }
+export type ProcessAnomalies = { zombies: Zombie[]; 
+/**
+ * Only processes reparented while tr-process was running can be told apart from
+ * children started by init directly.
+ */
+orphans: Orphan[]; missing_parents: MissingParent[] }
+export type ProcessExit = { pid: number; ppid?: number | null; name?: string | null; exe?: string | null; cpu_usage?: number | null; memory?: number | null; accumulated_cpu_time?: number | null; 
+/**
+ * Seconds the process had been running when it was last seen.
+ */
+runtime?: number | null; exit_code?: number | null; exited_at: number }
+export type ProcessInfo = { pid: number; ppid?: number | null; name?: string | null; exe?: string | null; cpu_usage?: number | null; memory?: number | null; open_files?: number | null; disk_usage?: DiskInfo | null; accumulated_cpu_time?: number | null; socks: SockInfo[]; uptime?: number | null; start_time?: number | null; 
+/**
+ * Set for children started through `spawn_process`.
+ */
+spawned?: boolean | null; 
+/**
+ * Breakdown from `smaps_rollup`, refreshed every `SMAPS_INTERVAL`.
+ */
+memory_detail?: MemoryDetail | null; cgroup?: CgroupInfo | null; 
+/**
+ * sysinfo's status, e.g. `Zombie`.
+ */
+status?: string | null; 
+/**
+ * Stand-in for a parent pid that isn't in the snapshot; every other field is unset.
+ */
+missing?: boolean | null; 
+/**
+ * Set by the sampler's integrity pass.
+ */
+integrity?: ExeIntegrity | null; namespaces?: Namespaces | null; 
+/**
+ * Name of the remote host the process runs on; `None` for this machine.
+ */
+host?: string | null }
+export type ProcessLimits = { pid: number; limits: Rlimit[]; oom_score?: number | null; oom_score_adj?: number | null }
+export type ReaperKind = "Init" | 
+/**
+ * A process that set `PR_SET_CHILD_SUBREAPER`, or the init of a nested pid namespace.
+ */
+"Subreaper"
+/**
+ * Another tr-process instance serving the HTTP API.
+ */
+export type RemoteHost = { name: string; 
+/**
+ * Base URL, e.g. `http://10.0.0.5:9090`.
+ */
+url: string; token?: string | null }
+/**
+ * Payload of the `remote-state` event.
+ */
+export type RemoteState = { name: string; url: string; status: RemoteStatus; last_error?: string | null; 
+/**
+ * Milliseconds since the unix epoch of the last successful poll.
+ */
+last_seen?: number | null; process_count?: number | null; 
+/**
+ * Milliseconds until the next attempt while in `Backoff`.
+ */
+retry_in?: number | null }
+export type RemoteStatus = "Connecting" | "Connected" | "Backoff" | "Stopped"
+export type Resource = "Cpu" | "Fsize" | "Data" | "Stack" | "Core" | "Rss" | "Nproc" | "Nofile" | "Memlock" | "As" | "Locks" | "Sigpending" | "Msgqueue" | "Nice" | "Rtprio" | "Rttime"
+export type RestartPolicy = "Always" | "OnFailure" | "Never"
+export type Rlimit = { 
+/**
+ * Row name as printed by the kernel, e.g. `Max open files`.
+ */
+name: string; resource?: Resource | null; soft: RlimitValue; hard: RlimitValue; units?: string | null }
+export type RlimitValue = "Unlimited" | { Value: number }
+export type SchedInfo = { pid: number; nice?: number | null; affinity?: number[] | null; io_priority?: IoPriority | null }
 export type ServInfo = { name: string; ip: string; port: number; path: string }
-export type SockInfo = { local_addr: string; local_port: number; protocol: SockProtocol; pids: number[]; remote_addr?: string | null; remote_port?: number | null; state?: SockState | null }
+export type ServiceDef = { name: string; spawn: SpawnRequest; restart: RestartPolicy; health_check?: HealthCheck | null; 
+/**
+ * First restart delay in milliseconds, doubled on every consecutive failure.
+ */
+backoff_initial?: number | null; backoff_max?: number | null }
+export type ServiceState = { name: string; status: ServiceStatus; pid?: number | null; restarts: number; last_exit_code?: number | null; last_error?: string | null; healthy?: boolean | null; 
+/**
+ * Milliseconds until the next restart while in `Backoff`.
+ */
+restart_in?: number | null }
+export type ServiceStatus = "Starting" | "Running" | "Backoff" | "Stopped" | 
+/**
+ * Exited and the restart policy says to leave it down.
+ */
+"Exited" | "Failed"
+export type SockInfo = { local_addr: string; local_port: number; protocol: SockProtocol; pids: number[]; remote_addr?: string | null; remote_port?: number | null; state?: SockState | null; 
+/**
+ * Network namespace inode, for sockets outside tr-process's own namespace.
+ */
+netns?: number | null }
 export type SockProtocol = "Tcp" | "Udp"
 export type SockState = "Closed" | "Listen" | "SynSent" | "SynReceived" | "Established" | "FinWait1" | "FinWait2" | "CloseWait" | "Closing" | "LastAck" | "TimeWait" | "DeleteTcb" | "Unknown"
+export type SpawnRequest = { cmd: string; args?: string[]; cwd?: string | null; env?: Partial<{ [key in string]: string }> | null }
+export type SpawnedChild = { pid: number; cmd: string; args: string[]; cwd?: string | null; status: ChildStatus }
+/**
+ * Totals for a process and all of its descendants.
+ */
+export type SubtreeMemory = { pid: number; process_count: number; rss: number; 
+/**
+ * Sum of PSS, which doesn't double-count shared pages; falls back to `memory` without a breakdown.
+ */
+pss: number; uss: number; swap: number }
+export type SystemOverview = { hostname?: string | null; kernel_version?: string | null; os_version?: string | null; 
+/**
+ * Seconds since boot.
+ */
+uptime: number; 
+/**
+ * Unix time of boot, in seconds.
+ */
+boot_time: number; cpu_usage: number; cpus: CpuCore[]; load_average: LoadAverage; total_memory: number; used_memory: number; available_memory: number; free_memory: number; total_swap: number; used_swap: number; disks: DiskUsage[]; networks: NetworkInterface[] }
+export type ViewKind = 
+/**
+ * Table, tree and graph side by side.
+ */
+"Split" | "Tree" | "Table" | "Graph"
+export type Zombie = { pid: number; name?: string | null; 
+/**
+ * The parent that hasn't reaped it yet.
+ */
+ppid?: number | null; parent_name?: string | null; parent_status?: string | null }
 
 /** tauri-specta globals **/
 
@@ -108,9 +926,3 @@ function __makeEvents__<T extends Record<string, any>>(
 		},
 	);
 }
-// This file has been generated by Specta. DO NOT EDIT.
-
-export type HttpCmd = "Refresh"
-
-export type HttpNotify = { cmd: HttpCmd; param?: string | null }
-
@@ -0,0 +1,49 @@
+import {useEffect, useState} from "react";
+import {listen} from "@tauri-apps/api/event";
+import {commands, RemoteState, RemoteStatus} from "@/bindings.ts";
+
+const STATUS_COLORS: Record<RemoteStatus, string> = {
+  Connected: '#2a9d8f',
+  Connecting: '#e9c46a',
+  Backoff: '#e76f51',
+  Stopped: '#999999',
+};
+
+function sort_remotes(remotes: RemoteState[]) {
+  return remotes.sort((a, b) => a.name.localeCompare(b.name));
+}
+
+// One entry per host whose processes are merged into the views; hidden without remote hosts.
+function HostStatusView() {
+  const [remotes, setRemotes] = useState<RemoteState[]>([]);
+
+  useEffect(() => {
+    commands.getRemotes().then((res) => {
+      if (res.status == 'ok') {
+        setRemotes(sort_remotes(res.data));
+      }
+    });
+    const unlisten = listen<RemoteState>('remote-state', (event) => {
+      const state = event.payload;
+      setRemotes((remotes) => sort_remotes([...remotes.filter((r) => r.name != state.name), state]));
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  if (remotes.length == 0) return null;
+
+  return (
+    <div className="hosts">
+      <div className="host" style={{borderColor: STATUS_COLORS.Connected}}>local</div>
+      {remotes.map((remote) => (
+        <div className="host" key={remote.name} title={remote.last_error ?? remote.url} style={{borderColor: STATUS_COLORS[remote.status]}}>
+          {remote.name}: {remote.status}{remote.process_count != undefined ? ` (${remote.process_count})` : ''}
+        </div>
+      ))}
+    </div>
+  )
+}
+
+export default HostStatusView;
@@ -3,7 +3,7 @@ import {commands, HttpNotify, ProcessInfo} from "@/bindings.ts";
 import cytoscape, {CollectionReturnValue, EventObject, NodeSingular} from 'cytoscape';
 import CytoscapeComponent from 'react-cytoscapejs';
 import {useElementsStore} from "@/stores/elementsStore.ts";
-import {get_mem} from "@/components/utils.ts";
+import {get_key, get_mem} from "@/components/utils.ts";
 import AutoSizer from 'react-virtualized-auto-sizer'
 import { FontAwesomeIcon as Icon } from '@fortawesome/react-fontawesome'
 import {
@@ -21,7 +21,7 @@ import {
   coseLayoutOptions,
   graphStylesheet,
 } from "@/components/graph.ts";
-import { useSelectedPidStore } from '@/stores/selectedPidStore';
+import { useSelectedKeyStore } from '@/stores/selectedKeyStore';
 import {useProcessesStore} from "@/stores/processesStore.ts";
 import {useTableStore} from "@/stores/tableStore.ts";
 import {useTableOrderStore} from "@/stores/tableOrderStore.ts";
@@ -33,8 +33,8 @@ function ProcessGraphView() {
   const table = useTableStore((state) => state.table);
   const elements = useElementsStore((state) => state.elements);
   const setElements = useElementsStore((state) => state.setElements);
-  const selectedPid = useSelectedPidStore((state) => state.selectedPid);
-  const setSelectedPid = useSelectedPidStore((state) => state.setSelectedPid);
+  const selectedKey = useSelectedKeyStore((state) => state.selectedKey);
+  const setSelectedKey = useSelectedKeyStore((state) => state.setSelectedKey);
   const cyInstance = useCyStore((state) => state.cyInstance);
   const setCyInstance = useCyStore((state) => state.setCyInstance);
   const [selectedItem, setSelectedItem] = useState<ProcessInfo | undefined>(undefined);
@@ -55,7 +55,7 @@ function ProcessGraphView() {
   const clickZoomIn = () => {
     const cy = cyInstance;
     if (!cy) return;
-    if (selectedPid != undefined) {
+    if (selectedKey != undefined) {
       cy.animate({
         zoom: cy.zoom() * 1.3,
         center: { eles: cy.elements(':selected') },
@@ -71,7 +71,7 @@ function ProcessGraphView() {
   const clickZoomOut = () => {
     const cy = cyInstance;
     if (!cy) return;
-    if (selectedPid != undefined) {
+    if (selectedKey != undefined) {
       cy.animate({
         zoom: cy.zoom() / 1.3,
         center: { eles: cy.elements(':selected') },
@@ -86,7 +86,7 @@ function ProcessGraphView() {
     const cy = cyInstance;
     if (!cy) return;
 
-    if (selectedPid != undefined) {
+    if (selectedKey != undefined) {
       const canvasWidth = cy.width();
       const canvasHeight = cy.height();
       const shorterSide = Math.min(canvasWidth, canvasHeight);
@@ -108,7 +108,7 @@ function ProcessGraphView() {
   const clickZoomMax = () => {
     const cy = cyInstance;
     if (!cy) return;
-    if (selectedPid != undefined) {
+    if (selectedKey != undefined) {
       const canvasWidth = cy.width();
       const canvasHeight = cy.height();
       const shorterSide = Math.min(canvasWidth, canvasHeight);
@@ -127,16 +127,16 @@ function ProcessGraphView() {
 
   const handleNodeSelect = useCallback((event: EventObject) => {
     const node = event.target;
-    setSelectedPid(node.data('pid'));
+    setSelectedKey(node.id());
   }, [])
   const handleNodeUnSelect = useCallback((_event: EventObject) => {
-    setSelectedPid(undefined);
+    setSelectedKey(undefined);
   }, [])
 
   const handleEdgeSelect = useCallback((_event: EventObject) => {
   }, [])
   const handleEdgeUnSelect = useCallback((_event: EventObject) => {
-    setSelectedPid(undefined);
+    setSelectedKey(undefined);
     setSelectedItem(undefined);
   }, [])
 
@@ -145,15 +145,16 @@ function ProcessGraphView() {
     if (!cyInstance) return;
     cyInstance.edges(':selected').unselect();
     cyInstance.nodes(':selected').unselect();
-    if (selectedPid == undefined) {
+    if (selectedKey == undefined) {
       return;
     }
 
     const cy = cyInstance;
-    const processInfo = cy.getElementById(`${selectedPid}`).data() as ProcessInfo;
+    // keys of remote processes contain a '/', which a `#id` selector would misread
+    const target = cy.getElementById(selectedKey);
+    const processInfo = target.data() as ProcessInfo;
     setSelectedItem(processInfo);
 
-    const target = cy.$(`#${selectedPid}`);
     target.select();
     const selectedNode = cy.$(':selected');
     if (target.length > 0) {
@@ -184,7 +185,7 @@ function ProcessGraphView() {
         current = parentNodes.first();
       }
     }
-  }, [selectedPid]);
+  }, [selectedKey]);
 
   const reCyRef = (cy: cytoscape.Core) => {
     if (cyRef.current) {
@@ -210,7 +211,7 @@ function ProcessGraphView() {
   };
 
   useEffect(() => {
-    commands.getProcess().then((res) => {
+    commands.getAllProcesses().then((res) => {
       if (res.status == 'ok') {
         const processes = res.data;
         setProcesses(processes);
@@ -224,11 +225,12 @@ function ProcessGraphView() {
     // if (processes == undefined) return;
     if (table == undefined) return;
 
+    const keys = new Set(table.map((process) => get_key(process)));
     const pidNodes: cytoscape.ElementDefinition[] = table.map(( process) => {
       const color = process.socks.length > 0 ? '#f4a261' : '#1f77b4';
       return {
         data: {
-          id: `${process.pid}`,
+          id: get_key(process),
           type: 'node',
           label: `${process.pid}`,
           color: color,
@@ -240,14 +242,16 @@ function ProcessGraphView() {
 
     const pidEdges: cytoscape.ElementDefinition[] = table
       .filter((process) => (process.ppid !== null && process.ppid !== undefined ))
-      // .filter((process) => processes.some((p)=> p.pid === process.parent))
+      // the parent may be filtered out of the table
+      .filter((process) => keys.has(get_key({ host: process.host, pid: process.ppid! })))
       .map((process) => {
+        const source = get_key({ host: process.host, pid: process.ppid! });
         return {
           data: {
-            id: `${process.ppid}-${process.pid}`,
+            id: `${source}-${get_key(process)}`,
             type: 'edge',
-            source: `${process.ppid}`,
-            target: `${process.pid}`,
+            source: source,
+            target: get_key(process),
             label: `${process.ppid}-${process.pid}`,
           },
           selectable: true
@@ -288,7 +292,7 @@ function ProcessGraphView() {
         <div className="zoom-in">
           <Icon icon={faMagnifyingGlassPlus} onClick={() => clickZoomIn()} />
         </div>
-        {(selectedPid != undefined) && (
+        {(selectedKey != undefined) && (
           <>
             <div className="zoom-max">
               <Icon icon={faMaximize} onClick={() => clickZoomMax()} />
@@ -303,7 +307,7 @@ function ProcessGraphView() {
           <div className="folder" onClick={() => shellShowItemInFolder(selectedItem.exe)}><Icon icon={faFolder} /></div>
         )}
         {selectedItem && (
-          <div className="label">[{selectedItem.pid}] {selectedItem.exe || selectedItem.name || ''}</div>
+          <div className="label">[{selectedItem.host ? `${selectedItem.host}/` : ''}{selectedItem.pid}] {selectedItem.exe || selectedItem.name || ''}</div>
         )}
 
       </div>
@@ -343,7 +347,7 @@ function get_info(process: ProcessInfo | undefined) {
     remote = `${remote_addr}:${remote_port}`;
   }
   const ret = [
-    `${process?.name}`,
+    `${process?.missing ? 'missing parent' : process?.name}`,
     `pid: ${process?.pid}`,
     `ppid: ${process?.ppid}`,
     `mem: ${get_mem(process?.memory)}`,
@@ -1,33 +1,33 @@
 import { FixedSizeList as List } from 'react-window';
 import AutoSizer from "react-virtualized-auto-sizer";
 import { useTableStore } from '@/stores/tableStore';
-import {get_mem, get_sec} from "@/components/utils.ts";
+import {get_key, get_mem, get_sec} from "@/components/utils.ts";
 import {useEffect, useRef} from "react";
 import {ProcessInfo} from "@/bindings.ts";
-import {useSelectedPidStore} from "@/stores/selectedPidStore.ts";
+import {useSelectedKeyStore} from "@/stores/selectedKeyStore.ts";
 import {get_local_addr, get_local_port} from "@/components/ProcessGraphView.tsx";
 
 
 const ITEM_SIZE = 18;
 function ProcessTableListView() {
   const table = useTableStore((state) => state.table);
-  const selectedPid = useSelectedPidStore((state) => state.selectedPid);
-  const setSelectedPid = useSelectedPidStore((state) => state.setSelectedPid);
+  const selectedKey = useSelectedKeyStore((state) => state.selectedKey);
+  const setSelectedKey = useSelectedKeyStore((state) => state.setSelectedKey);
   const listRef = useRef<List>(null);
 
-  const clickItem = (pid: number | undefined | null) => {
+  const clickItem = (host: string | undefined | null, pid: number | undefined | null) => {
     if (pid == undefined) return;
-    setSelectedPid(pid);
+    setSelectedKey(get_key({ host, pid }));
   }
 
   useEffect(() => {
-    if (selectedPid == undefined || !listRef.current) return;
-    const idx = table?.findIndex((item) => item.pid == selectedPid);
-    if (idx == undefined) {
+    if (selectedKey == undefined || !listRef.current) return;
+    const idx = table?.findIndex((item) => get_key(item) == selectedKey);
+    if (idx == undefined || idx < 0) {
       return
     }
     listRef.current.scrollToItem(idx, "center");
-  }, [selectedPid]);
+  }, [selectedKey]);
 
   if (table === undefined) return null;
 
@@ -46,14 +46,15 @@ function ProcessTableListView() {
             {({ index, style }) => {
               const item: ProcessInfo = table[index];
               return item ? (
-                <div className="row" key={index} style={{...style, backgroundColor: `${selectedPid == item.pid ? '#bfd2e3': null}`}} >
-                  <div className="col ppid" onClick={() => clickItem(item.ppid)}>{item.ppid || ''}</div>
-                  <div className="col pid" onClick={() => clickItem(item.pid)}>{item.pid}</div>
-                  <div className="col name" title={getTitle(item)} onClick={() => clickItem(item.pid)}>{item.name || ''}</div>
-                  <div className="col addr" onClick={() => clickItem(item.pid)}>{get_local_addr(item) || ''}</div>
-                  <div className="col port" onClick={() => clickItem(item.pid)}>{get_local_port(item) || ''}</div>
-                  <div className="col memory" onClick={() => clickItem(item.pid)}>{get_mem(item.memory) || ''}</div>
-                  <div className="col uptime" onClick={() => clickItem(item.pid)}>{get_sec(item.uptime) || ''}</div>
+                <div className="row" key={get_key(item)} style={{...style, backgroundColor: `${selectedKey == get_key(item) ? '#bfd2e3': null}`}} >
+                  <div className="col ppid" onClick={() => clickItem(item.host, item.ppid)}>{item.ppid || ''}</div>
+                  <div className="col pid" onClick={() => clickItem(item.host, item.pid)}>{item.pid}</div>
+                  <div className="col name" title={getTitle(item)} onClick={() => clickItem(item.host, item.pid)}>{item.host ? `${item.host}: ` : ''}{item.name || ''}</div>
+                  <div className="col addr" onClick={() => clickItem(item.host, item.pid)}>{get_local_addr(item) || ''}</div>
+                  <div className="col port" onClick={() => clickItem(item.host, item.pid)}>{get_local_port(item) || ''}</div>
+                  <div className="col memory" onClick={() => clickItem(item.host, item.pid)}>{get_mem(item.memory) || ''}</div>
+                  <div className="col pss" onClick={() => clickItem(item.host, item.pid)}>{get_mem(item.memory_detail?.pss) || ''}</div>
+                  <div className="col uptime" onClick={() => clickItem(item.host, item.pid)}>{get_sec(item.uptime) || ''}</div>
                 </div>
               ) : null
             }}
@@ -67,5 +68,6 @@ function ProcessTableListView() {
 export default ProcessTableListView;
 
 function getTitle(item: ProcessInfo): string {
-  return item.exe || item.name || String(item.pid);
+  const title = item.exe || item.name || String(item.pid);
+  return item.host ? `${item.host}: ${title}` : title;
 }
@@ -9,12 +9,26 @@ import { useTableOrderStore } from '@/stores/tableOrderStore';
 import {OrdAsc, OrdBy, OrdItm, sort_items} from "@/components/ordering.ts";
 import ProcessTableListView from "@/components/ProcessTableListView.tsx";
 import {useTableStore} from "@/stores/tableStore.ts";
-import {HttpNotify} from "@/bindings.ts";
+import {HttpNotify, ProcessInfo} from "@/bindings.ts";
 import {useProcessesStore} from "@/stores/processesStore.ts";
+import {useFilterStore} from "@/stores/filterStore.ts";
 import {emit} from "@tauri-apps/api/event";
+import HostStatusView from "@/components/HostStatusView.tsx";
+
+// Matches the name or host case-insensitively, or the pid or a local port exactly.
+function filter_items(processes: ProcessInfo[], filter: string): ProcessInfo[] {
+  const text = filter.toLowerCase();
+  return processes.filter((p) =>
+    p.name?.toLowerCase().includes(text)
+    || p.host?.toLowerCase().includes(text)
+    || String(p.pid) == text
+    || p.socks.some((s) => String(s.local_port) == text)
+  );
+}
 
 function ProcessTableView() {
   const processes = useProcessesStore((state) => state.processes);
+  const filter = useFilterStore((state) => state.filter);
   const setTable = useTableStore((state) => state.setTable);
   const tableOrder = useTableOrderStore((state) => state.tableOrder);
   const setTableOrder = useTableOrderStore((state) => state.setTableOrder);
@@ -32,16 +46,17 @@ function ProcessTableView() {
 
   useEffect(() => {
     if (processes == undefined) return;
+    const items = filter ? filter_items(processes, filter) : processes;
     let ordering: OrdItm[] = [
       tableOrder,
       { nm: "Ppid", asc: 'Asc' },
       { nm: "Pid", asc: 'Asc' },
       { nm: "Name", asc: 'Asc' },
     ]
-    const sorted_items = sort_items(processes, ordering);
+    const sorted_items = sort_items(items, ordering);
     setTable([...sorted_items]);
-    setTable(processes);
-  }, [processes]);
+    setTable(items);
+  }, [processes, filter]);
 
   useEffect(() => {
     const httpNotify: HttpNotify = {
@@ -57,6 +72,7 @@ function ProcessTableView() {
   let iconAddr = faCircleMinus
   let iconPort = faCircleMinus
   let iconMemory = faCircleMinus
+  let iconPss = faCircleMinus
   let iconUptime = faCircleMinus
 
   if (tableOrder.nm == 'Pid') {
@@ -71,12 +87,15 @@ function ProcessTableView() {
     iconPort = tableOrder.asc == 'Asc' ? faCircleChevronUp : faCircleChevronDown
   } else if (tableOrder.nm == 'Memory') {
     iconMemory = tableOrder.asc == 'Asc' ? faCircleChevronUp : faCircleChevronDown
+  } else if (tableOrder.nm == 'Pss') {
+    iconPss = tableOrder.asc == 'Asc' ? faCircleChevronUp : faCircleChevronDown
   } else if (tableOrder.nm == 'Uptime') {
     iconUptime = tableOrder.asc == 'Asc' ? faCircleChevronUp : faCircleChevronDown
   }
 
   return (
     <div className="table-pane">
+      <HostStatusView />
       <div className="header">
         <div className="col ppid"><Icon icon={iconPpid} onClick={() => clickOrder('Ppid')} />ppid</div>
         <div className="col pid"><Icon icon={iconPid} onClick={() => clickOrder('Pid')} />pid</div>
@@ -84,6 +103,7 @@ function ProcessTableView() {
         <div className="col addr"><Icon icon={iconAddr} onClick={() => clickOrder('Addr')} />addr</div>
         <div className="col port"><Icon icon={iconPort} onClick={() => clickOrder('Port')} />port</div>
         <div className="col memory"><Icon icon={iconMemory} onClick={() => clickOrder('Memory')} />memory</div>
+        <div className="col pss"><Icon icon={iconPss} onClick={() => clickOrder('Pss')} />pss</div>
         <div className="col uptime"><Icon icon={iconUptime} onClick={() => clickOrder('Uptime')} />uptime</div>
       </div>
       <ProcessTableListView />
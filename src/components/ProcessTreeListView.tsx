@@ -4,7 +4,7 @@ import {useEffect, useRef, useState} from "react";
 import {get_mem, get_sec} from "@/components/utils.ts";
 import {EdgeSingular, NodeSingular} from "cytoscape";
 import {useCyStore} from "@/stores/cyStore.ts";
-import {useSelectedPidStore} from "@/stores/selectedPidStore.ts";
+import {useSelectedKeyStore} from "@/stores/selectedKeyStore.ts";
 import natsort from "natsort";
 import {OrdItm} from "@/components/ordering.ts";
 import {useTableOrderStore} from "@/stores/tableOrderStore.ts";
@@ -14,15 +14,15 @@ const ITEM_SIZE = 18;
 function ProcessTreeListView() {
   const cyInstance = useCyStore((state) => state.cyInstance);
   const listRef = useRef<List>(null);
-  const setSelectedPid = useSelectedPidStore((state) => state.setSelectedPid);
-  const selectedPid = useSelectedPidStore((state) => state.selectedPid);
+  const setSelectedKey = useSelectedKeyStore((state) => state.setSelectedKey);
+  const selectedKey = useSelectedKeyStore((state) => state.selectedKey);
   const [rootNodes, setRootNodes] = useState<NodeSingular[] | undefined>(undefined);
   const [selectedItem, setSelectedItem] = useState<NodeSingular | undefined>(undefined);
   const tableOrder = useTableOrderStore((state) => state.tableOrder);
   const [treeOrder, setTreeOrder] = useState<OrdItm[] | undefined>([])
 
   const clickItem = (item: NodeSingular | undefined) => {
-    setSelectedPid(item?.data('pid'));
+    setSelectedKey(item?.id());
   }
 
   useEffect(() => {
@@ -37,15 +37,15 @@ function ProcessTreeListView() {
 
   useEffect(() => {
     if (!cyInstance || !listRef.current || !treeOrder) return;
-    const processInfo = cyInstance.getElementById(`${selectedPid}`) as NodeSingular;
+    const processInfo = cyInstance.getElementById(`${selectedKey}`) as NodeSingular;
     setSelectedItem(processInfo);
 
-    const idx = getIndexFromItem(rootNodes, cyInstance.getElementById(`${selectedPid}`));
+    const idx = getIndexFromItem(rootNodes, processInfo);
     if (idx == undefined) {
       return;
     }
     listRef.current.scrollToItem(idx, "center");
-  }, [selectedPid]);
+  }, [selectedKey]);
 
   useEffect(() => {
     if (!cyInstance) return;
@@ -74,7 +74,7 @@ function ProcessTreeListView() {
               const [item, _idx, depth] = getItemFromIndex(rootNodes, index);
               const dep = Array.from({ length: depth }, (_, i) => i);
               return item ? (
-              <div className="row-tree" key={index} style={{...style, backgroundColor: `${selectedItem?.data('pid') == String(item.data('pid')) ? '#bfd2e3': null}`}} >
+              <div className="row-tree" key={index} style={{...style, backgroundColor: `${selectedItem?.nonempty() && selectedItem.id() == item.id() ? '#bfd2e3': null}`}} >
                 {dep.map((depIdx)=> {
                   return (
                   <div className="depth" key={depIdx} onClick={() => clickItem(getParentItem(item, (depth - depIdx)))}>
@@ -83,7 +83,7 @@ function ProcessTreeListView() {
                 )})}
                 <div className="row">
                   <div className="col pid" onClick={() => clickItem(item)}>{item.data('pid')}</div>
-                  <div className="col name" title={getTitle(item)} onClick={() => clickItem(item)}>{item.data('name') || ''}</div>
+                  <div className="col name" title={getTitle(item)} onClick={() => clickItem(item)}>{item.data('host') ? `${item.data('host')}: ` : ''}{item.data('name') || ''}</div>
                   <div className="col addr" onClick={() => clickItem(item)}>{item.data('local_addr') || ''}</div>
                   <div className="col port" onClick={() => clickItem(item)}>{item.data('local_port') || ''}</div>
                   <div className="col memory" onClick={() => clickItem(item)}>{get_mem(item.data('memory')) || ''}</div>
@@ -150,7 +150,7 @@ function getNthFromItem(treeItems: NodeSingular[] |undefined, item: NodeSingular
 
   for (let idxItem = 0; idxItem < treeItems.length; idxItem++) {
     curIdx++
-    if (treeItems[idxItem].data('pid') == item.data('pid')) {
+    if (treeItems[idxItem].id() == item.id()) {
       findIndex = curIdx
       break
     }
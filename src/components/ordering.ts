@@ -3,7 +3,7 @@ import {ProcessInfo} from "@/bindings.ts";
 import {get_local_addr, get_local_port} from "@/components/ProcessGraphView.tsx";
 
 export type OrdAsc = "Asc" | "Desc";
-export type OrdBy = "Pid" | "Ppid" | "Name" | "Addr" | "Port" | "Memory" | "Uptime";
+export type OrdBy = "Pid" | "Ppid" | "Name" | "Addr" | "Port" | "Memory" | "Pss" | "Uptime";
 export type OrdItm = { nm: OrdBy; asc: OrdAsc };
 
 
@@ -34,6 +34,9 @@ export function sort_items(items: ProcessInfo[], ordItms: OrdItm[]) {
       } else if (key === 'Memory') {
         valA = a.memory?.toString() ?? '';
         valB = b.memory?.toString() ?? '';
+      } else if (key === 'Pss') {
+        valA = a.memory_detail?.pss?.toString() ?? '';
+        valB = b.memory_detail?.pss?.toString() ?? '';
       } else if (key === 'Addr') {
         valA = get_local_addr(a)?.toString() ?? '';
         valB = get_local_addr(b)?.toString() ?? '';
@@ -17,4 +17,8 @@ export function get_sec(uptime: number | undefined | null) {
   return formatInTimeZone(date, 'UTC', 'HH:mm:ss')
   // date.setSeconds(uptime)
   // return format(date, 'HH:mm:ss')
-}
\ No newline at end of file
+}
+// Pids repeat across hosts once remote processes are merged in, so views key processes by host and pid.
+export function get_key(process: { host?: string | null, pid: number }) {
+  return process.host ? `${process.host}/${process.pid}` : `${process.pid}`
+}
@@ -1,30 +1,106 @@
 import {useEffect} from "react";
 import {listen} from "@tauri-apps/api/event";
-import {commands, HttpNotify} from "@/bindings";
+import {commands, HttpCmd, HttpNotify, HttpReply, JsonValue} from "@/bindings";
 import {useElementsStore} from "@/stores/elementsStore.ts";
-import {useSelectedPidStore} from "@/stores/selectedPidStore.ts";
+import {useSelectedKeyStore} from "@/stores/selectedKeyStore.ts";
 import {useProcessesStore} from "@/stores/processesStore.ts";
+import {useFilterStore} from "@/stores/filterStore.ts";
+import {useViewStore} from "@/stores/viewStore.ts";
+import {get_key} from "@/components/utils.ts";
+
+const PROTOCOL_VERSION = 1;
+
+type CmdResult = { ok: true, result?: JsonValue } | { ok: false, error: string };
 
 function HttpNotifyListener() {
   const setElements = useElementsStore((state) => state.setElements);
-  const setSelectedPid = useSelectedPidStore((state) => state.setSelectedPid);
+  const setSelectedKey = useSelectedKeyStore((state) => state.setSelectedKey);
   const setProcesses = useProcessesStore((state) => state.setProcesses);
+  const setFilter = useFilterStore((state) => state.setFilter);
+  const setView = useViewStore((state) => state.setView);
 
-  useEffect(() => {
-    const unlisten = listen<HttpNotify>('http', (event) => {
-      let taskNotify = event.payload;
-      if (taskNotify.cmd === "Refresh") {
-        // setSelectedPid(undefined);
-        setElements(undefined);
+  const refresh = async (): Promise<CmdResult> => {
+    // setSelectedKey(undefined);
+    setElements(undefined);
 
-        commands.getProcess().then((res) => {
-          if (res.status == 'ok') {
-            const processes = res.data;
-            setProcesses(processes);
-          }
-        });
+    const res = await commands.getAllProcesses();
+    if (res.status == 'ok') {
+      setProcesses(res.data);
+      return { ok: true };
+    }
+    return { ok: false, error: res.error.message };
+  }
 
+  const handle = async (cmd: HttpCmd): Promise<CmdResult> => {
+    const processes = useProcessesStore.getState().processes ?? [];
+    // pids in commands refer to this machine
+    const local = processes.filter((p) => !p.host);
+    if (cmd === "Refresh") {
+      return refresh();
+    } else if (cmd === "ExportSnapshot") {
+      const selectedKey = useSelectedKeyStore.getState().selectedKey;
+      const selected = processes.find((p) => get_key(p) == selectedKey);
+      return {
+        ok: true,
+        result: {
+          processes: processes as JsonValue,
+          selected_pid: selected?.pid ?? null,
+          selected_host: selected?.host ?? null,
+          filter: useFilterStore.getState().filter ?? null,
+          view: useViewStore.getState().view,
+        },
+      };
+    } else if ("SelectPid" in cmd) {
+      const pid = cmd.SelectPid.pid;
+      if (!local.some((p) => p.pid == pid)) {
+        return { ok: false, error: `Process ${pid} not found` };
+      }
+      setSelectedKey(get_key({ pid }));
+      return { ok: true };
+    } else if ("FocusPort" in cmd) {
+      const port = cmd.FocusPort.port;
+      const process = local.find((p) => p.socks.some((s) => s.local_port == port));
+      if (process == undefined) {
+        return { ok: false, error: `No process on port ${port}` };
       }
+      setSelectedKey(get_key(process));
+      return { ok: true, result: { pid: process.pid } };
+    } else if ("ApplyFilter" in cmd) {
+      setFilter(cmd.ApplyFilter.text ?? undefined);
+      return { ok: true };
+    } else if ("SwitchView" in cmd) {
+      setView(cmd.SwitchView.view);
+      return { ok: true };
+    } else if ("KillProcess" in cmd) {
+      const pid = cmd.KillProcess.pid;
+      const name = local.find((p) => p.pid == pid)?.name;
+      if (!window.confirm(`Kill process ${pid}${name ? ` (${name})` : ""}? Requested through the HTTP API.`)) {
+        return { ok: false, error: `Killing process ${pid} was declined` };
+      }
+      const res = await commands.killProcess(pid);
+      if (res.status == 'ok') {
+        return refresh();
+      }
+      return { ok: false, error: res.error.message };
+    }
+    return { ok: false, error: `Unknown command ${JSON.stringify(cmd)}` };
+  }
+
+  useEffect(() => {
+    const unlisten = listen<HttpNotify>('http', (event) => {
+      let taskNotify = event.payload;
+      handle(taskNotify.cmd).then((res) => {
+        // commands emitted from the window itself carry no id and expect no reply
+        if (taskNotify.id == undefined) return;
+        const reply: HttpReply = {
+          id: taskNotify.id,
+          version: PROTOCOL_VERSION,
+          ok: res.ok,
+          result: res.ok ? res.result : undefined,
+          error: res.ok ? undefined : res.error,
+        };
+        commands.replyHttp(reply).then();
+      });
     });
     return () => {
       unlisten.then((f) => f());
@@ -34,4 +110,4 @@ function HttpNotifyListener() {
   return null;
 }
 
-export default HttpNotifyListener;
\ No newline at end of file
+export default HttpNotifyListener;
@@ -3,10 +3,20 @@ import { SplitPane } from '@rexxars/react-split-pane'
 import ProcessTableView from "@/components/ProcessTableView.tsx";
 import ProcessTreeView from "@/components/ProcessTreeView.tsx";
 import ProcessGraphView from "@/components/ProcessGraphView.tsx";
+import {useViewStore} from "@/stores/viewStore.ts";
 
 function ProcessView() {
   const [isResizing, setIsResizing] = useState(false);
   const [isResizing2, setIsResizing2] = useState(false);
+  const view = useViewStore((state) => state.view);
+
+  if (view == 'Table') {
+    return <div className="main-pane"><ProcessTableView /></div>
+  } else if (view == 'Tree') {
+    return <div className="main-pane"><ProcessTreeView /></div>
+  } else if (view == 'Graph') {
+    return <div className="main-pane" style={{ position: 'relative', height: '100%' }}><ProcessGraphView /></div>
+  }
 
   return (
     <div className="main-pane">
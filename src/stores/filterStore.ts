@@ -0,0 +1,11 @@
+import { create } from "zustand"
+
+export interface FilterStore {
+  filter: string | undefined,
+  setFilter: (filter?: string) => void
+}
+
+export const useFilterStore = create<FilterStore>((set) => ({
+  filter: undefined,
+  setFilter: (filter?: string) => set(() => ({ filter }))
+}))
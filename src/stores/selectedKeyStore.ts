@@ -0,0 +1,12 @@
+import { create } from "zustand"
+
+export interface SelectedKeyStore {
+  /** `get_key` of the selected process. */
+  selectedKey: string | undefined | null,
+  setSelectedKey: (selectedKey: string | undefined) => void
+}
+
+export const useSelectedKeyStore = create<SelectedKeyStore>((set) => ({
+  selectedKey: undefined,
+  setSelectedKey: (selectedKey: string | undefined) => set(() => ({ selectedKey }))
+}))
@@ -0,0 +1,12 @@
+import { create } from "zustand"
+import {ViewKind} from "@/bindings.ts";
+
+export interface ViewStore {
+  view: ViewKind,
+  setView: (view: ViewKind) => void
+}
+
+export const useViewStore = create<ViewStore>((set) => ({
+  view: "Split",
+  setView: (view: ViewKind) => set(() => ({ view }))
+}))